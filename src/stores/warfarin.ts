@@ -1,11 +1,11 @@
 import { defineStore } from 'pinia';
 import { nextTick, ref } from 'vue';
 
-import type { AvailablePills, RegimenOption } from '@/types';
+import type { AvailablePills, RegimenOption, SnappedDose, SuggestionDiagnostics } from '@/types';
 
 import { DEFAULT_AVAILABLE_PILLS } from '@/constants/pills';
 
-import init, { generate_suggestions_rust } from '../../warfarin_logic/pkg/warfarin_logic.js';
+import init, { diagnose_suggestions_rust, generate_suggestions_rust, snap_weekly_dose_rust } from '../../warfarin_logic/pkg/warfarin_logic.js';
 import { useAppointmentStore } from './appointment';
 
 export const useWarfarinStore = defineStore('warfarin', () => {
@@ -19,6 +19,26 @@ export const useWarfarinStore = defineStore('warfarin', () => {
   const errorMsg = ref('');
   const wasmReady = ref(false);
 
+  // --- Helpers ---
+  function selectedPillStrengths() {
+    return Object.keys(availablePills.value)
+      .filter(key => availablePills.value[Number(key)])
+      .map(Number);
+  }
+
+  function buildInput(dose: number) {
+    const appointmentStore = useAppointmentStore();
+    const appointmentInfo = appointmentStore.appointmentInfo;
+    return {
+      weekly_dose: dose,
+      allow_half: allowHalf.value,
+      available_pills: selectedPillStrengths(),
+      special_day_pattern: specialDayPattern.value,
+      days_until_appointment: appointmentInfo.daysUntilAppointment,
+      start_day_of_week: appointmentInfo.startDayOfWeek,
+    };
+  }
+
   // --- Actions ---
   async function initWasm() {
     try {
@@ -39,10 +59,7 @@ export const useWarfarinStore = defineStore('warfarin', () => {
       return;
     }
 
-    const selectedPills = Object.keys(availablePills.value)
-      .filter(key => availablePills.value[Number(key)]);
-
-    if (selectedPills.length === 0) {
+    if (selectedPillStrengths().length === 0) {
       errorMsg.value = 'กรุณาเลือกขนาดยาอย่างน้อย 1 ขนาด';
       results.value = [];
       return;
@@ -56,23 +73,17 @@ export const useWarfarinStore = defineStore('warfarin', () => {
     await new Promise(r => setTimeout(r, 400));
 
     try {
-      const appointmentStore = useAppointmentStore();
-      const appointmentInfo = appointmentStore.appointmentInfo;
-
-      const input = {
-        weekly_dose: Number.parseFloat(String(weeklyDose.value)),
-        allow_half: allowHalf.value,
-        available_pills: selectedPills.map(Number),
-        special_day_pattern: specialDayPattern.value,
-        days_until_appointment: appointmentInfo.daysUntilAppointment,
-        start_day_of_week: appointmentInfo.startDayOfWeek,
-      };
+      const input = buildInput(Number.parseFloat(String(weeklyDose.value)));
 
       const rustResults = await generate_suggestions_rust(input);
       results.value = rustResults;
 
       if (rustResults.length === 0) {
-        errorMsg.value = 'ไม่พบตัวเลือกที่เหมาะสมสำหรับเงื่อนไขนี้ (ลองอนุญาตให้ใช้ครึ่งเม็ด)';
+        const diagnostics: SuggestionDiagnostics = diagnose_suggestions_rust(input);
+        const remedies = diagnostics.remedies.map(remedy => remedy.message);
+        errorMsg.value = remedies.length > 0
+          ? `ไม่พบตัวเลือกที่เหมาะสมสำหรับเงื่อนไขนี้ ลอง: ${remedies.join(', ')}`
+          : 'ไม่พบตัวเลือกที่เหมาะสมสำหรับเงื่อนไขนี้';
       }
       else {
         nextTick(() => {
@@ -91,6 +102,20 @@ export const useWarfarinStore = defineStore('warfarin', () => {
     }
   }
 
+  /** Sets the weekly dose to the achievable dose nearest to `target`. */
+  function snapWeeklyDose(target: number) {
+    let snapped: SnappedDose | null = null;
+    if (wasmReady.value && selectedPillStrengths().length > 0) {
+      try {
+        snapped = snap_weekly_dose_rust(buildInput(target));
+      }
+      catch (e) {
+        console.error('Error calling Rust WASM function:', e);
+      }
+    }
+    weeklyDose.value = snapped?.nearest?.weekly_dose ?? Math.round(target * 2) / 2;
+  }
+
   function clearResults() {
     results.value = [];
     errorMsg.value = '';
@@ -110,6 +135,7 @@ export const useWarfarinStore = defineStore('warfarin', () => {
     // Actions
     initWasm,
     handleCalculation,
+    snapWeeklyDose,
     clearResults,
   };
 });
@@ -11,13 +11,83 @@ export type AppointmentInfo = {
   startDayOfWeek: number;
 };
 
+export type Indication =
+  | 'atrial-fibrillation'
+  | 'provoked-venous-thromboembolism'
+  | 'unprovoked-venous-thromboembolism'
+  | 'mechanical-aortic-valve'
+  | 'mechanical-mitral-valve'
+  | 'mechanical-valve-high-risk'
+  | 'bioprosthetic-valve'
+  | 'left-ventricular-thrombus'
+  | 'antiphospholipid-syndrome';
+
+export type VkaDrug = 'warfarin' | 'acenocoumarol' | 'phenprocoumon';
+
 export type CalculationInput = {
+  drug?: VkaDrug;
   weekly_dose: number;
   allow_half: boolean;
   available_pills: number[];
   special_day_pattern: string;
   days_until_appointment: number;
   start_day_of_week: number;
+  inr_history?: DoseInrRecord[];
+  previous_weekly_dose?: number;
+  indication?: Indication;
+  target_inr_low?: number;
+  target_inr_high?: number;
+  stock?: PillStock[];
+  exclude_out_of_stock?: boolean;
+  home_supply?: HomeSupply[];
+  packs?: PackSize[];
+  rounding?: 'exact' | 'strip' | 'box';
+  buffer?: SupplyBuffer;
+  prices?: TabletPrice[];
+  rank_by?: 'simplicity' | 'cost' | 'minimal-change';
+  current_regimen?: DaySchedule[];
+  max_tablets_per_day?: number;
+};
+
+export type TabletPrice = {
+  mg: number;
+  price: number;
+};
+
+export type SupplyBuffer = {
+  extra_days?: number;
+  extra_percent?: number;
+  travel_days?: number;
+};
+
+export type PackSize = {
+  mg: number;
+  tablets_per_strip: number;
+  strips_per_box: number;
+};
+
+export type HomeSupply = {
+  mg: number;
+  whole?: number;
+  halves?: number;
+};
+
+export type PillStock = {
+  mg: number;
+  quantity: number;
+};
+
+export type DoseInrRecord = {
+  date: string;
+  weekly_dose: number;
+  inr: number;
+};
+
+export type InrPrediction = {
+  predicted_inr: number;
+  lower_95: number;
+  upper_95: number;
+  extrapolated: boolean;
 };
 
 export type PillRenderData = {
@@ -28,6 +98,7 @@ export type PillRenderData = {
 
 export type DaySchedule = {
   day_index: number;
+  week_index: number;
   total_dose: number;
   pills: PillRenderData[];
   is_stop_day: boolean;
@@ -36,18 +107,144 @@ export type DaySchedule = {
 
 export type PillLineSummary = {
   mg: number;
+  required_count: number;
   dispensed_count: number;
+  surplus_count: number;
+  packs?: { boxes: number; strips: number; loose_tablets: number };
   usage_note: string;
 };
 
 export type TotalPillsSummary = {
   header: string;
   pill_lines: PillLineSummary[];
+  buffer?: { header: string; days: number; pill_lines: PillLineSummary[] };
+  supply_mismatches: { mg: number; expected_tablets: number; on_hand_tablets: number; message: string }[];
+};
+
+export type SafetyWarning = {
+  rule: string;
+  severity: 'info' | 'warning' | 'critical';
+  message: string;
 };
 
 export type RegimenOption = {
   description: string;
   weekly_dose_actual: number;
+  cycle_weeks: number;
   weekly_schedule: DaySchedule[];
   total_pills_summary: TotalPillsSummary;
+  predicted_inr?: InrPrediction;
+  warnings: SafetyWarning[];
+  stock_shortfalls: { mg: number; needed: number; available: number }[];
+  stock_alternative?: { short_strengths: number[]; description: string; message: string } | null;
+  remaining_stock: { mg: number; remaining: number }[];
+  cost?: { per_week: number; per_appointment: number } | null;
+  change?: RegimenChange | null;
+};
+
+export type RegimenChange = {
+  distance: number;
+  changed_days: { week_index: number; day_index: number; current_dose: number; new_dose: number }[];
+  strengths_added: number[];
+  strengths_removed: number[];
+  new_half_strengths: number[];
+  description: string;
+};
+
+export type ParseRegimenInput = {
+  drug?: VkaDrug;
+  text?: string;
+  schedule?: DaySchedule[];
+  available_pills?: number[];
+  days_until_appointment?: number;
+  start_day_of_week?: number;
+};
+
+export type ParsedRegimen = {
+  regimen: RegimenOption;
+  complexity: {
+    half_tablet_strengths: number;
+    tablet_strengths: number;
+    tablets_per_week: number;
+    distinct_daily_doses: number;
+  };
+  unrecognized_words: string[];
+};
+
+export type DoseChange = { percent: number } | { mg: number };
+
+export type DoseComparisonInput = CalculationInput & {
+  adjustments: DoseChange[];
+};
+
+export type DoseGroup = {
+  adjustment: DoseChange;
+  requested_weekly_dose: number;
+  weekly_dose: number | null;
+  percent_change: number | null;
+  label: string;
+  regimens: RegimenOption[];
+};
+
+export type DoseLadderInput = {
+  drug?: VkaDrug;
+  available_pills: number[];
+  allow_half: boolean;
+  special_day_pattern?: 'fri-sun' | 'mon-wed-fri';
+  from_weekly_dose: number;
+  to_weekly_dose: number;
+  step_mg?: number;
+  format?: 'html' | 'csv' | 'text';
+};
+
+export type DoseLadder = {
+  rows: {
+    weekly_dose: number;
+    description: string | null;
+    tablets_per_week: number | null;
+    step_percent: number | null;
+  }[];
+  gaps: number[];
+  table: string | null;
+};
+
+export type AchievableDose = {
+  weekly_dose: number;
+  deviation_percent: number;
+};
+
+export type SnappedDose = {
+  target_weekly_dose: number;
+  below: AchievableDose | null;
+  above: AchievableDose | null;
+  nearest: AchievableDose | null;
+};
+
+export type RejectionReason =
+  | 'negative-dose'
+  | 'not-constructible'
+  | 'duplicate-half'
+  | 'above-dose-multiplier'
+  | 'above-daily-max'
+  | 'alternating-not-allowed'
+  | 'out-of-stock';
+
+export type RemedyAction =
+  | 'allow-half'
+  | 'include-out-of-stock'
+  | { 'enable-strength': number }
+  | { 'allow-tablets-per-day': number }
+  | { 'change-special-day-pattern': 'fri-sun' | 'mon-wed-fri' }
+  | { 'use-weekly-dose': number };
+
+export type SuggestionDiagnostics = {
+  weekly_dose: number;
+  has_results: boolean;
+  rejections: {
+    reason: RejectionReason;
+    count: number;
+    daily_doses: number[];
+    message: string;
+  }[];
+  remedies: { action: RemedyAction; message: string }[];
 };
@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+
+use crate::packs::apply_pack_rounding;
+use crate::{
+    calculate_total_pills, CalculationInput, DosageOption, PillLineSummary, FLOAT_TOLERANCE,
+};
+
+/// Extra days of tablets on top of the appointment interval, for a visit that
+/// might slip or a patient who will be away.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct SupplyBuffer {
+    pub extra_days: u32,
+    pub extra_percent: f64, // of `days_until_appointment`, rounded up to whole days
+    pub travel_days: u32,   // travel supply: the whole trip is covered as well
+}
+
+/// Tablets dispensed for the buffer days, kept apart from the appointment supply.
+#[derive(Serialize, Debug, Clone)]
+pub struct BufferSummary {
+    pub header: String,
+    pub days: u32,
+    pub pill_lines: Vec<PillLineSummary>,
+}
+
+impl SupplyBuffer {
+    /// Buffer days beyond an appointment interval of `days_until_appointment`.
+    pub fn days(&self, days_until_appointment: u32) -> u32 {
+        let percent_days =
+            (days_until_appointment as f64 * self.extra_percent / 100.0 - FLOAT_TOLERANCE).ceil();
+        self.extra_days + percent_days.max(0.0) as u32 + self.travel_days
+    }
+}
+
+/// Tablets for the buffer days, found by carrying the dated schedule on past the
+/// appointment, so a half tablet left over from the appointment supply is used
+/// first. Rounded to packs on their own: the appointment lines' rounding surplus
+/// is not counted towards the buffer.
+pub(crate) fn buffer_summary(
+    option: &DosageOption,
+    input: &CalculationInput,
+) -> Option<BufferSummary> {
+    let days = input.buffer.days(input.days_until_appointment);
+    if days == 0 {
+        return None;
+    }
+    let supply_for = |days: u32| {
+        calculate_total_pills(
+            option.daily_combos(input.start_day_of_week, days),
+            &input.home_supply,
+        )
+    };
+    let appointment = supply_for(input.days_until_appointment);
+    let extended = supply_for(input.days_until_appointment + days);
+
+    let mut buffer_lines: Vec<PillLineSummary> = extended
+        .into_iter()
+        .filter_map(|line| {
+            let (required, dispensed) = appointment
+                .iter()
+                .find(|a| a.mg == line.mg)
+                .map_or((0, 0), |a| (a.required_count, a.dispensed_count));
+            let required_count = line.required_count.saturating_sub(required);
+            (required_count > 0).then_some(PillLineSummary {
+                mg: line.mg,
+                required_count,
+                dispensed_count: line.dispensed_count.saturating_sub(dispensed),
+                surplus_count: 0,
+                packs: None,
+                usage_note: String::new(),
+            })
+        })
+        .collect();
+    apply_pack_rounding(&mut buffer_lines, &input.packs, input.rounding);
+
+    let header = if input.buffer.travel_days > 0 {
+        format!(
+            "ยาสำรองเพิ่ม ({} วัน รวมเผื่อเดินทาง {} วัน):",
+            days, input.buffer.travel_days
+        )
+    } else {
+        format!("ยาสำรองเพิ่ม ({} วัน):", days)
+    };
+    Some(BufferSummary {
+        header,
+        days,
+        pill_lines: buffer_lines,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::{PackSize, RoundingPolicy};
+    use crate::{option_supply, OptionType, Pill};
+
+    fn daily(mg: u8, half: bool) -> DosageOption {
+        let combo = vec![Pill { mg, count: 1, half }];
+        let dose = if half { mg as f64 / 2.0 } else { mg as f64 };
+        DosageOption::new(OptionType::Uniform(combo), dose * 7.0)
+    }
+
+    fn input(days_until_appointment: u32, buffer: SupplyBuffer) -> CalculationInput {
+        CalculationInput {
+            days_until_appointment,
+            buffer,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn counts_extra_percent_and_travel_days() {
+        let buffer = SupplyBuffer {
+            extra_days: 2,
+            extra_percent: 10.0,
+            travel_days: 5,
+        };
+        // 10% of 28 days is 2.8, rounded up to 3.
+        assert_eq!(buffer.days(28), 10);
+        assert_eq!(buffer.days(30), 10);
+        assert_eq!(SupplyBuffer::default().days(28), 0);
+    }
+
+    #[test]
+    fn buffer_lines_are_rounded_to_strips() {
+        let mut input = input(
+            14,
+            SupplyBuffer {
+                extra_days: 7,
+                ..Default::default()
+            },
+        );
+        input.packs = vec![PackSize {
+            mg: 5,
+            tablets_per_strip: 10,
+            strips_per_box: 10,
+        }];
+        input.rounding = RoundingPolicy::Strip;
+        let (lines, buffer) = option_supply(&daily(5, false), &input);
+        assert_eq!(
+            (
+                lines[0].required_count,
+                lines[0].dispensed_count,
+                lines[0].surplus_count
+            ),
+            (14, 20, 6)
+        );
+        let buffer = buffer.unwrap();
+        let line = &buffer.pill_lines[0];
+        assert_eq!(
+            (
+                line.required_count,
+                line.dispensed_count,
+                line.surplus_count
+            ),
+            (7, 10, 3)
+        );
+        let packs = line.packs.as_ref().unwrap();
+        assert_eq!((packs.strips, packs.loose_tablets), (1, 0));
+    }
+
+    #[test]
+    fn leftover_half_tablet_goes_to_the_buffer() {
+        // 2.5 mg a day: 3 days need 1.5 tablets, so 2 are dispensed.
+        let input = input(
+            3,
+            SupplyBuffer {
+                extra_days: 1,
+                ..Default::default()
+            },
+        );
+        let (lines, buffer) = option_supply(&daily(5, true), &input);
+        assert_eq!(lines[0].dispensed_count, 2);
+        // The fourth day's half is the one left over, so nothing more is dispensed.
+        assert!(buffer.unwrap().pill_lines.is_empty());
+    }
+}
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// A calendar date without time zone, exchanged with JS as "YYYY-MM-DD"
+/// (the format of `<input type="date">`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CalendarDate {
+    days_since_epoch: i64, // 1970-01-01 = 0
+}
+
+impl CalendarDate {
+    pub fn from_ymd(year: i64, month: u32, day: u32) -> Option<Self> {
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+        // Days-from-civil (proleptic Gregorian calendar).
+        let y = if month <= 2 { year - 1 } else { year };
+        let era = y.div_euclid(400);
+        let yoe = y - era * 400;
+        let mp = (month as i64 + 9) % 12;
+        let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        Some(CalendarDate {
+            days_since_epoch: era * 146_097 + doe - 719_468,
+        })
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = text.trim().split('-').collect();
+        let invalid = || format!("วันที่ไม่ถูกต้อง: {}", text);
+        if parts.len() != 3 {
+            return Err(invalid());
+        }
+        let year = parts[0].parse::<i64>().map_err(|_| invalid())?;
+        let month = parts[1].parse::<u32>().map_err(|_| invalid())?;
+        let day = parts[2].parse::<u32>().map_err(|_| invalid())?;
+        CalendarDate::from_ymd(year, month, day).ok_or_else(invalid)
+    }
+
+    pub fn ymd(&self) -> (i64, u32, u32) {
+        let z = self.days_since_epoch + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+        (year, month, day)
+    }
+
+    pub fn add_days(&self, days: i64) -> Self {
+        CalendarDate {
+            days_since_epoch: self.days_since_epoch + days,
+        }
+    }
+
+    /// Signed number of days from `self` to `other`.
+    pub fn days_until(&self, other: CalendarDate) -> i64 {
+        other.days_since_epoch - self.days_since_epoch
+    }
+
+    /// 0=Mon, 1=Tue, ..., 6=Sun (same convention as `start_day_of_week`).
+    pub fn day_of_week(&self) -> usize {
+        // 1970-01-01 was a Thursday.
+        (self.days_since_epoch + 3).rem_euclid(7) as usize
+    }
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl fmt::Display for CalendarDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = self.ymd();
+        write!(f, "{:04}-{:02}-{:02}", year, month, day)
+    }
+}
+
+impl Serialize for CalendarDate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for CalendarDate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        CalendarDate::parse(&text).map_err(serde::de::Error::custom)
+    }
+}
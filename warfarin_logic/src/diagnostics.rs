@@ -0,0 +1,358 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use wasm_bindgen::prelude::*;
+
+use crate::snap::has_regimen;
+use crate::{
+    generate_options_recording, prepare_calculation_input, snap_weekly_dose, stock_shortfalls_for,
+    CalculationInput, SpecialDayPattern, DOSE_MULTIPLIER_LIMIT, MAX_TABLETS_PER_DAY_LIMIT,
+};
+
+// Daily doses quoted in a rejection message, nearest the uniform daily dose first.
+const QUOTED_DOSES: usize = 5;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+pub enum RejectionReason {
+    NegativeDose,
+    NotConstructible, // no combination within the tablets-per-day limit
+    DuplicateHalf,    // only combinations with two halves of one strength
+    AboveDoseMultiplier,
+    AboveDailyMax,
+    AlternatingNotAllowed,
+    OutOfStock,
+}
+
+/// How often the generator dropped a candidate for one reason, and at which daily doses.
+#[derive(Serialize, Debug, Clone)]
+pub struct RejectionSummary {
+    pub reason: RejectionReason,
+    pub count: u32,
+    pub daily_doses: Vec<f64>,
+    pub message: String,
+}
+
+/// A setting change that is known to produce at least one regimen.
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum RemedyAction {
+    AllowHalf,
+    EnableStrength(u8),
+    AllowTabletsPerDay(u8),
+    ChangeSpecialDayPattern(SpecialDayPattern),
+    IncludeOutOfStock,
+    UseWeeklyDose(f64),
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Remedy {
+    pub action: RemedyAction,
+    pub message: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SuggestionDiagnostics {
+    pub weekly_dose: f64,
+    pub has_results: bool,
+    pub rejections: Vec<RejectionSummary>,
+    pub remedies: Vec<Remedy>, // empty when there are results
+}
+
+/// Why the generator dropped candidates, filled in as it searches.
+#[derive(Default, Debug)]
+pub(crate) struct Rejections {
+    reasons: BTreeMap<RejectionReason, (u32, BTreeSet<i64>)>, // count, daily doses in 0.01 mg
+}
+
+impl Rejections {
+    pub(crate) fn record(&mut self, reason: RejectionReason, daily_dose: f64) {
+        let (count, doses) = self.reasons.entry(reason).or_default();
+        *count += 1;
+        doses.insert((daily_dose * 100.0).round() as i64);
+    }
+
+    pub(crate) fn count(&mut self, reason: RejectionReason) {
+        self.reasons.entry(reason).or_default().0 += 1;
+    }
+
+    fn summaries(&self, input: &CalculationInput) -> Vec<RejectionSummary> {
+        let profile = input.drug.profile();
+        let daily_target = input.weekly_dose / 7.0;
+        self.reasons
+            .iter()
+            .map(|(&reason, (count, doses))| {
+                let daily_doses: Vec<f64> = doses.iter().map(|&d| d as f64 / 100.0).collect();
+                let mut quoted = daily_doses.clone();
+                quoted.sort_by(|a, b| {
+                    (a - daily_target)
+                        .abs()
+                        .total_cmp(&(b - daily_target).abs())
+                });
+                quoted.truncate(QUOTED_DOSES);
+                quoted.sort_by(f64::total_cmp);
+                let mut quoted = quoted
+                    .iter()
+                    .map(|d| d.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                if daily_doses.len() > QUOTED_DOSES {
+                    quoted.push_str(", …");
+                }
+                let message = match reason {
+                    RejectionReason::NegativeDose => "ขนาดยาต่อสัปดาห์ติดลบ".to_string(),
+                    RejectionReason::NotConstructible => format!(
+                        "ขนาดยาต่อวัน {} mg จัดไม่ได้ด้วยยาที่เลือกไม่เกิน {} เม็ด",
+                        quoted,
+                        input.max_tablets()
+                    ),
+                    RejectionReason::DuplicateHalf => format!(
+                        "ขนาดยาต่อวัน {} mg ต้องใช้ครึ่งเม็ดของขนาดเดียวกันมากกว่า 1 ชิ้น",
+                        quoted
+                    ),
+                    RejectionReason::AboveDoseMultiplier => format!(
+                        "ขนาดยา {} mg ในวันพิเศษเกิน {} เท่าของวันปกติ",
+                        quoted, DOSE_MULTIPLIER_LIMIT
+                    ),
+                    RejectionReason::AboveDailyMax => format!(
+                        "ขนาดยา {} mg ในวันพิเศษเกินขนาดสูงสุด {} mg/วัน ของ{}",
+                        quoted, profile.max_daily_dose_mg, profile.name
+                    ),
+                    RejectionReason::AlternatingNotAllowed => {
+                        format!("{}มีค่าครึ่งชีวิตสั้น จึงไม่จัดแบบวันเว้นวัน", profile.name)
+                    }
+                    RejectionReason::OutOfStock => {
+                        format!("ตัดออก {} ตัวเลือกเพราะยาในคลังไม่พอ", count)
+                    }
+                };
+                RejectionSummary {
+                    reason,
+                    count: *count,
+                    daily_doses,
+                    message,
+                }
+            })
+            .collect()
+    }
+}
+
+#[wasm_bindgen]
+pub fn diagnose_suggestions_rust(input_js: JsValue) -> Result<JsValue, JsValue> {
+    let mut input: CalculationInput =
+        serde_wasm_bindgen::from_value(input_js).map_err(|e| e.to_string())?;
+    prepare_calculation_input(&mut input)?;
+    Ok(serde_wasm_bindgen::to_value(&diagnose_suggestions(&input))?)
+}
+
+/// Explains an empty `generate_suggestions`: what the generator rejected and which
+/// single setting changes would give at least one regimen, each checked by running
+/// the generator again. Expects the same input as `generate_suggestions`. A negative
+/// weekly dose has no remedy to offer.
+pub fn diagnose_suggestions(input: &CalculationInput) -> SuggestionDiagnostics {
+    let mut rejections = Rejections::default();
+    let options = generate_options_recording(input, &mut rejections);
+    if input.exclude_out_of_stock && !input.stock.is_empty() {
+        for opt in &options {
+            if !stock_shortfalls_for(opt, input).is_empty() {
+                rejections.count(RejectionReason::OutOfStock);
+            }
+        }
+    }
+    let has_results = has_regimen(input, input.weekly_dose);
+    SuggestionDiagnostics {
+        weekly_dose: input.weekly_dose,
+        has_results,
+        rejections: rejections.summaries(input),
+        remedies: if has_results || input.weekly_dose < 0.0 {
+            Vec::new()
+        } else {
+            find_remedies(input)
+        },
+    }
+}
+
+fn find_remedies(input: &CalculationInput) -> Vec<Remedy> {
+    let profile = input.drug.profile();
+    let works = |candidate: CalculationInput| has_regimen(&candidate, candidate.weekly_dose);
+    let mut remedies = Vec::new();
+    let mut push =
+        |action: RemedyAction, message: String| remedies.push(Remedy { action, message });
+
+    if !input.allow_half
+        && works(CalculationInput {
+            allow_half: true,
+            ..input.clone()
+        })
+    {
+        push(RemedyAction::AllowHalf, "อนุญาตให้ใช้ครึ่งเม็ด".to_string());
+    }
+    for &mg in profile.strengths_mg {
+        if input.available_pills.contains(&mg) {
+            continue;
+        }
+        let mut available_pills = input.available_pills.clone();
+        available_pills.push(mg);
+        available_pills.sort_unstable_by(|a, b| b.cmp(a));
+        if works(CalculationInput {
+            available_pills,
+            ..input.clone()
+        }) {
+            push(
+                RemedyAction::EnableStrength(mg),
+                format!("เปิดใช้ยาเม็ด {} mg", mg),
+            );
+        }
+    }
+    if let Some(tablets) = (input.max_tablets() + 1..=MAX_TABLETS_PER_DAY_LIMIT).find(|&tablets| {
+        works(CalculationInput {
+            max_tablets_per_day: Some(tablets),
+            ..input.clone()
+        })
+    }) {
+        push(
+            RemedyAction::AllowTabletsPerDay(tablets),
+            format!("อนุญาตให้ใช้ยาได้ถึง {} เม็ด/วัน", tablets),
+        );
+    }
+    let (pattern, pattern_label) = match input.special_day_pattern {
+        SpecialDayPattern::FriSun => (SpecialDayPattern::MonWedFri, "จ./พ./ศ."),
+        SpecialDayPattern::MonWedFri => (SpecialDayPattern::FriSun, "ศ./ส./อา."),
+    };
+    if works(CalculationInput {
+        special_day_pattern: pattern,
+        ..input.clone()
+    }) {
+        push(
+            RemedyAction::ChangeSpecialDayPattern(pattern),
+            format!("ย้ายวันพิเศษไปเป็น {}", pattern_label),
+        );
+    }
+    if input.exclude_out_of_stock
+        && !input.stock.is_empty()
+        && works(CalculationInput {
+            exclude_out_of_stock: false,
+            ..input.clone()
+        })
+    {
+        push(
+            RemedyAction::IncludeOutOfStock,
+            "แสดงตัวเลือกที่ยาในคลังไม่พอด้วย".to_string(),
+        );
+    }
+    if let Some(nearest) = snap_weekly_dose(input).nearest {
+        push(
+            RemedyAction::UseWeeklyDose(nearest.weekly_dose),
+            format!(
+                "ปรับขนาดยาเป็น {:.1} mg/สัปดาห์ ({:+.1}%)",
+                nearest.weekly_dose, nearest.deviation_percent
+            ),
+        );
+    }
+    remedies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_suggestions, PillStock};
+
+    fn apply(input: &CalculationInput, action: RemedyAction) -> CalculationInput {
+        let mut applied = input.clone();
+        match action {
+            RemedyAction::AllowHalf => applied.allow_half = true,
+            RemedyAction::EnableStrength(mg) => applied.available_pills.push(mg),
+            RemedyAction::AllowTabletsPerDay(tablets) => {
+                applied.max_tablets_per_day = Some(tablets)
+            }
+            RemedyAction::ChangeSpecialDayPattern(pattern) => applied.special_day_pattern = pattern,
+            RemedyAction::IncludeOutOfStock => applied.exclude_out_of_stock = false,
+            RemedyAction::UseWeeklyDose(dose) => applied.weekly_dose = dose,
+        }
+        prepare_calculation_input(&mut applied).unwrap();
+        applied
+    }
+
+    fn diagnose(input: CalculationInput) -> (CalculationInput, SuggestionDiagnostics) {
+        let mut input = CalculationInput {
+            days_until_appointment: 28,
+            ..input
+        };
+        prepare_calculation_input(&mut input).unwrap();
+        let diagnostics = diagnose_suggestions(&input);
+        (input, diagnostics)
+    }
+
+    #[test]
+    fn every_remedy_gives_a_regimen() {
+        let cases = [
+            CalculationInput {
+                weekly_dose: 17.5,
+                available_pills: vec![5],
+                ..Default::default()
+            },
+            CalculationInput {
+                weekly_dose: 52.5,
+                available_pills: vec![3],
+                max_tablets_per_day: Some(2),
+                ..Default::default()
+            },
+            CalculationInput {
+                weekly_dose: 35.0,
+                available_pills: vec![5],
+                stock: vec![PillStock {
+                    mg: 5,
+                    quantity: 10,
+                }],
+                exclude_out_of_stock: true,
+                ..Default::default()
+            },
+        ];
+        for case in cases {
+            let (input, diagnostics) = diagnose(case);
+            assert!(generate_suggestions(&input).is_empty());
+            assert!(!diagnostics.has_results);
+            assert!(!diagnostics.rejections.is_empty());
+            assert!(!diagnostics.remedies.is_empty());
+            for remedy in &diagnostics.remedies {
+                let applied = apply(&input, remedy.action);
+                assert!(
+                    !generate_suggestions(&applied).is_empty(),
+                    "{:?}",
+                    remedy.action
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn no_remedies_when_there_are_results() {
+        let (_, diagnostics) = diagnose(CalculationInput {
+            weekly_dose: 35.0,
+            available_pills: vec![5],
+            ..Default::default()
+        });
+        assert!(diagnostics.has_results);
+        assert!(diagnostics.remedies.is_empty());
+    }
+
+    #[test]
+    fn out_of_stock_options_are_counted() {
+        let (_, diagnostics) = diagnose(CalculationInput {
+            weekly_dose: 35.0,
+            available_pills: vec![5],
+            stock: vec![PillStock {
+                mg: 5,
+                quantity: 10,
+            }],
+            exclude_out_of_stock: true,
+            ..Default::default()
+        });
+        assert!(diagnostics
+            .rejections
+            .iter()
+            .any(|r| r.reason == RejectionReason::OutOfStock && r.count > 0));
+        assert!(diagnostics
+            .remedies
+            .iter()
+            .any(|r| matches!(r.action, RemedyAction::IncludeOutOfStock)));
+    }
+}
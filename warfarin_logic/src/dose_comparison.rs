@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    generate_suggestions, prepare_calculation_input, snap_weekly_dose, CalculationInput,
+    FinalOutput, FLOAT_TOLERANCE,
+};
+
+/// A change from the base weekly dose, e.g. `{ "percent": -10 }` or `{ "mg": 2.5 }`.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum DoseChange {
+    Percent(f64),
+    Mg(f64),
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct DoseComparisonInput {
+    #[serde(flatten)]
+    pub calculation: CalculationInput, // `weekly_dose` is the base dose
+    pub adjustments: Vec<DoseChange>,
+}
+
+/// The regimens for one adjustment, labelled with the change actually achieved.
+#[derive(Serialize)]
+pub struct DoseGroup {
+    pub adjustment: DoseChange,
+    pub requested_weekly_dose: f64,
+    pub weekly_dose: Option<f64>, // None when no dose is achievable
+    pub percent_change: Option<f64>,
+    pub label: String,
+    pub regimens: Vec<FinalOutput>,
+}
+
+#[wasm_bindgen]
+pub fn compare_dose_adjustments_rust(input_js: JsValue) -> Result<JsValue, JsValue> {
+    let mut input: DoseComparisonInput =
+        serde_wasm_bindgen::from_value(input_js).map_err(|e| e.to_string())?;
+    prepare_calculation_input(&mut input.calculation)?;
+    Ok(serde_wasm_bindgen::to_value(&compare_dose_adjustments(
+        &input,
+    )?)?)
+}
+
+/// One group per adjustment, in the order given. Each adjusted dose is snapped to
+/// the nearest weekly dose the available tablets can build, so "-10%" may come
+/// back as "-8.6%".
+pub fn compare_dose_adjustments(input: &DoseComparisonInput) -> Result<Vec<DoseGroup>, String> {
+    let base = input.calculation.weekly_dose;
+    if base <= 0.0 {
+        return Err("ขนาดยาเดิมต้องมากกว่า 0".to_string());
+    }
+    Ok(input
+        .adjustments
+        .iter()
+        .map(|&adjustment| {
+            let requested_weekly_dose = match adjustment {
+                DoseChange::Percent(percent) => base * (1.0 + percent / 100.0),
+                DoseChange::Mg(mg) => base + mg,
+            };
+            let weekly_dose = snap_weekly_dose(&CalculationInput {
+                weekly_dose: requested_weekly_dose,
+                ..input.calculation.clone()
+            })
+            .nearest
+            .map(|nearest| nearest.weekly_dose);
+            let percent_change = weekly_dose.map(|dose| (dose - base) / base * 100.0);
+            let label = match percent_change {
+                Some(percent) if percent.abs() < FLOAT_TOLERANCE => "เท่าเดิม".to_string(),
+                Some(percent) => format!("{:+.1}%", percent),
+                None => "ไม่มีขนาดยาที่จัดได้".to_string(),
+            };
+            let regimens = weekly_dose.map_or_else(Vec::new, |dose| {
+                generate_suggestions(&CalculationInput {
+                    weekly_dose: dose,
+                    ..input.calculation.clone()
+                })
+            });
+            DoseGroup {
+                adjustment,
+                requested_weekly_dose,
+                weekly_dose,
+                percent_change,
+                label,
+                regimens,
+            }
+        })
+        .collect())
+}
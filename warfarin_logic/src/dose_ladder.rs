@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::table::{Table, TableFormat};
+use crate::{
+    generate_options, CalculationInput, SpecialDayPattern, VkaDrug, DAY_NAMES, FLOAT_TOLERANCE,
+};
+
+// Keeps a mistyped range or step from sweeping for minutes.
+const MAX_LADDER_ROWS: usize = 500;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct DoseLadderInput {
+    #[serde(default)]
+    pub drug: VkaDrug,
+    pub available_pills: Vec<u8>,
+    pub allow_half: bool,
+    #[serde(default)]
+    pub special_day_pattern: SpecialDayPattern,
+    pub from_weekly_dose: f64,
+    pub to_weekly_dose: f64,
+    #[serde(default)]
+    pub step_mg: Option<f64>, // defaults to the drug's dose step
+    #[serde(default)]
+    pub format: Option<TableFormat>,
+}
+
+/// The simplest regimen at one weekly dose, or a gap when none can be built.
+#[derive(Serialize, Debug, Clone)]
+pub struct LadderRow {
+    pub weekly_dose: f64,
+    pub description: Option<String>,
+    pub tablets_per_week: Option<u32>,
+    pub step_percent: Option<f64>, // from the previous row that has a regimen
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DoseLadder {
+    pub rows: Vec<LadderRow>,
+    pub gaps: Vec<f64>,        // weekly doses with no regimen
+    pub table: Option<String>, // rendered in the requested `format`
+}
+
+#[wasm_bindgen]
+pub fn generate_dose_ladder_rust(input_js: JsValue) -> Result<JsValue, JsValue> {
+    let mut input: DoseLadderInput =
+        serde_wasm_bindgen::from_value(input_js).map_err(|e| e.to_string())?;
+    input.available_pills.sort_unstable_by(|a, b| b.cmp(a));
+    input
+        .drug
+        .profile()
+        .check_strengths(&input.available_pills)?;
+    Ok(serde_wasm_bindgen::to_value(&generate_dose_ladder(
+        &input,
+    )?)?)
+}
+
+/// Sweeps weekly doses from `from_weekly_dose` to `to_weekly_dose` and takes the
+/// top-ranked regimen at each, as for a printed dose chart. Expects
+/// `available_pills` sorted descending.
+pub fn generate_dose_ladder(input: &DoseLadderInput) -> Result<DoseLadder, String> {
+    let step = input.step_mg.unwrap_or(input.drug.profile().dose_step_mg);
+    if step <= 0.0 {
+        return Err("ระยะห่างของขนาดยาต้องมากกว่า 0".to_string());
+    }
+    if input.from_weekly_dose < 0.0 || input.from_weekly_dose > input.to_weekly_dose {
+        return Err("ช่วงขนาดยาไม่ถูกต้อง".to_string());
+    }
+    let steps =
+        ((input.to_weekly_dose - input.from_weekly_dose) / step + FLOAT_TOLERANCE).floor() as usize;
+    if steps >= MAX_LADDER_ROWS {
+        return Err(format!(
+            "ตารางยาวเกิน {} แถว กรุณาเพิ่มระยะห่างหรือลดช่วงขนาดยา",
+            MAX_LADDER_ROWS
+        ));
+    }
+
+    let mut rows: Vec<LadderRow> = Vec::new();
+    let mut previous_dose: Option<f64> = None;
+    for i in 0..=steps {
+        let weekly_dose = input.from_weekly_dose + i as f64 * step;
+        let option = generate_options(&CalculationInput {
+            drug: input.drug,
+            weekly_dose,
+            allow_half: input.allow_half,
+            available_pills: input.available_pills.clone(),
+            special_day_pattern: input.special_day_pattern,
+            ..Default::default()
+        })
+        .into_iter()
+        .next();
+        let step_percent = option.as_ref().and(previous_dose).and_then(|previous| {
+            (previous > 0.0).then(|| (weekly_dose - previous) / previous * 100.0)
+        });
+        if option.is_some() {
+            previous_dose = Some(weekly_dose);
+        }
+        rows.push(LadderRow {
+            weekly_dose,
+            description: option.as_ref().map(|opt| opt.get_description(&DAY_NAMES)),
+            tablets_per_week: option.as_ref().map(|opt| opt.total_pill_objects),
+            step_percent,
+        });
+    }
+
+    let gaps = rows
+        .iter()
+        .filter(|row| row.description.is_none())
+        .map(|row| row.weekly_dose)
+        .collect();
+    let mut ladder = DoseLadder {
+        rows,
+        gaps,
+        table: None,
+    };
+    ladder.table = input.format.map(|format| ladder.render(format));
+    Ok(ladder)
+}
+
+const HEADERS: [&str; 4] = ["ขนาดยา/สัปดาห์ (mg)", "เปลี่ยน", "จำนวนเม็ด/สัปดาห์", "ตารางยา"];
+const GAP_TEXT: &str = "ไม่มีตารางยาที่จัดได้";
+
+impl DoseLadder {
+    pub fn render(&self, format: TableFormat) -> String {
+        Table {
+            headers: HEADERS,
+            rows: self
+                .rows
+                .iter()
+                .map(|row| (row_cells(row), row.description.is_none().then_some("gap")))
+                .collect(),
+        }
+        .render(format)
+    }
+}
+
+fn row_cells(row: &LadderRow) -> [String; 4] {
+    [
+        format!("{:.1}", row.weekly_dose),
+        row.step_percent
+            .map_or_else(String::new, |percent| format!("{:+.1}%", percent)),
+        row.tablets_per_week
+            .map_or_else(String::new, |count| count.to_string()),
+        row.description
+            .clone()
+            .unwrap_or_else(|| GAP_TEXT.to_string()),
+    ]
+}
@@ -0,0 +1,231 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::date::CalendarDate;
+
+const MIN_HISTORY_POINTS: usize = 4;
+const MIN_DISTINCT_DOSES: usize = 2;
+// Predictions this far outside the observed dose range are marked as extrapolated.
+const EXTRAPOLATION_MARGIN: f64 = 0.2;
+
+/// Two-sided 95% Student's t critical values for 1..=30 degrees of freedom.
+const T_CRITICAL_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+    2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+    2.052, 2.048, 2.045, 2.042,
+];
+const Z_95: f64 = 1.96;
+
+/// An INR measured while the patient was on `weekly_dose`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DoseInrRecord {
+    pub date: CalendarDate,
+    pub weekly_dose: f64,
+    pub inr: f64,
+}
+
+/// Per-patient log-linear fit: ln(INR) = intercept + slope * weekly dose.
+#[derive(Serialize, Debug, Clone)]
+pub struct DoseResponseModel {
+    pub intercept: f64,
+    pub slope: f64,
+    pub residual_sd: f64,
+    pub points: usize,
+    pub min_dose: f64,
+    pub max_dose: f64,
+    mean_dose: f64,
+    dose_sum_of_squares: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct InrPrediction {
+    pub predicted_inr: f64,
+    pub lower_95: f64,
+    pub upper_95: f64,
+    pub extrapolated: bool,
+}
+
+#[wasm_bindgen]
+pub fn fit_dose_response_rust(history_js: JsValue) -> Result<JsValue, JsValue> {
+    let history: Vec<DoseInrRecord> =
+        serde_wasm_bindgen::from_value(history_js).map_err(|e| e.to_string())?;
+    let model = fit_dose_response(&history)?;
+    Ok(serde_wasm_bindgen::to_value(&model)?)
+}
+
+/// Fits the patient's own dose–response by least squares on ln(INR), skipping
+/// records with a non-positive or non-finite dose or INR. Refuses when there are
+/// too few results, too few distinct doses, or the INR does not rise
+/// with the dose.
+pub fn fit_dose_response(history: &[DoseInrRecord]) -> Result<DoseResponseModel, String> {
+    let points: Vec<(f64, f64)> = history
+        .iter()
+        .filter(|r| r.inr.is_finite() && r.inr > 0.0)
+        .filter(|r| r.weekly_dose.is_finite() && r.weekly_dose > 0.0)
+        .map(|r| (r.weekly_dose, r.inr.ln()))
+        .collect();
+    if points.len() < MIN_HISTORY_POINTS {
+        return Err(format!(
+            "ต้องมีผล INR อย่างน้อย {} ครั้งเพื่อทำนาย INR",
+            MIN_HISTORY_POINTS
+        ));
+    }
+    let mut distinct_doses: Vec<f64> = points.iter().map(|&(dose, _)| dose).collect();
+    distinct_doses.sort_by(f64::total_cmp);
+    distinct_doses.dedup_by(|a, b| (*a - *b).abs() < 0.01);
+    if distinct_doses.len() < MIN_DISTINCT_DOSES {
+        return Err("ต้องมีผล INR จากขนาดยาอย่างน้อย 2 ขนาดเพื่อทำนาย INR".to_string());
+    }
+
+    let n = points.len() as f64;
+    let mean_dose = points.iter().map(|&(dose, _)| dose).sum::<f64>() / n;
+    let mean_log_inr = points.iter().map(|&(_, log_inr)| log_inr).sum::<f64>() / n;
+    let dose_sum_of_squares: f64 = points
+        .iter()
+        .map(|&(dose, _)| (dose - mean_dose).powi(2))
+        .sum();
+    let cross_products: f64 = points
+        .iter()
+        .map(|&(dose, log_inr)| (dose - mean_dose) * (log_inr - mean_log_inr))
+        .sum();
+    let slope = cross_products / dose_sum_of_squares;
+    if slope <= 0.0 {
+        return Err("ผล INR ไม่เพิ่มขึ้นตามขนาดยา จึงไม่สามารถทำนาย INR ได้".to_string());
+    }
+    let intercept = mean_log_inr - slope * mean_dose;
+    let residual_sum_of_squares: f64 = points
+        .iter()
+        .map(|&(dose, log_inr)| (log_inr - (intercept + slope * dose)).powi(2))
+        .sum();
+
+    Ok(DoseResponseModel {
+        intercept,
+        slope,
+        residual_sd: (residual_sum_of_squares / (n - 2.0)).sqrt(),
+        points: points.len(),
+        min_dose: distinct_doses[0],
+        max_dose: distinct_doses[distinct_doses.len() - 1],
+        mean_dose,
+        dose_sum_of_squares,
+    })
+}
+
+impl DoseResponseModel {
+    /// Predicted INR with a 95% prediction interval for a single future test.
+    pub fn predict(&self, weekly_dose: f64) -> InrPrediction {
+        let log_inr = self.intercept + self.slope * weekly_dose;
+        let standard_error = self.residual_sd
+            * (1.0
+                + 1.0 / self.points as f64
+                + (weekly_dose - self.mean_dose).powi(2) / self.dose_sum_of_squares)
+                .sqrt();
+        let degrees_of_freedom = self.points - 2;
+        let t = T_CRITICAL_95
+            .get(degrees_of_freedom - 1)
+            .copied()
+            .unwrap_or(Z_95);
+        let dose_range = self.max_dose - self.min_dose;
+
+        InrPrediction {
+            predicted_inr: log_inr.exp(),
+            lower_95: (log_inr - t * standard_error).exp(),
+            upper_95: (log_inr + t * standard_error).exp(),
+            extrapolated: weekly_dose < self.min_dose - dose_range * EXTRAPOLATION_MARGIN
+                || weekly_dose > self.max_dose + dose_range * EXTRAPOLATION_MARGIN,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(weekly_dose: f64, log_inr: f64) -> DoseInrRecord {
+        DoseInrRecord {
+            date: CalendarDate::from_ymd(2026, 1, 1).unwrap(),
+            weekly_dose,
+            inr: log_inr.exp(),
+        }
+    }
+
+    fn history() -> Vec<DoseInrRecord> {
+        vec![
+            record(20.0, 0.6),
+            record(20.0, 0.8),
+            record(30.0, 1.0),
+            record(30.0, 1.2),
+        ]
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn fits_and_predicts_a_hand_computed_case() {
+        // Mean dose 25, Sxx 100, Sxy 4: slope 0.04, intercept -0.1, residuals ±0.1.
+        let model = fit_dose_response(&history()).unwrap();
+        assert_close(model.slope, 0.04);
+        assert_close(model.intercept, -0.1);
+        assert_close(model.residual_sd, 0.02_f64.sqrt());
+
+        // SE = sqrt(0.02) * sqrt(1 + 1/4), t(2 df) = 4.303.
+        let prediction = model.predict(25.0);
+        assert_close(prediction.predicted_inr, 0.9_f64.exp());
+        assert_close(prediction.lower_95, 1.245623);
+        assert_close(prediction.upper_95, 4.856724);
+        assert!(!prediction.extrapolated);
+        assert!(model.predict(40.0).extrapolated);
+    }
+
+    #[test]
+    fn refuses_fewer_than_four_points() {
+        assert!(fit_dose_response(&history()[..3]).is_err());
+    }
+
+    #[test]
+    fn refuses_a_single_dose() {
+        let history: Vec<DoseInrRecord> = (0..5)
+            .map(|i| record(20.0, 0.7 + i as f64 * 0.05))
+            .collect();
+        assert!(fit_dose_response(&history).is_err());
+    }
+
+    #[test]
+    fn refuses_an_inr_that_falls_with_the_dose() {
+        let history = vec![
+            record(20.0, 1.2),
+            record(20.0, 1.0),
+            record(30.0, 0.8),
+            record(30.0, 0.6),
+        ];
+        assert!(fit_dose_response(&history).is_err());
+    }
+
+    #[test]
+    fn skips_non_finite_records() {
+        let mut history = history();
+        history.push(DoseInrRecord {
+            weekly_dose: f64::INFINITY,
+            ..record(25.0, 0.9)
+        });
+        history.push(DoseInrRecord {
+            inr: f64::NAN,
+            ..record(25.0, 0.9)
+        });
+        let model = fit_dose_response(&history).unwrap();
+        assert_eq!(model.points, 4);
+        assert_close(model.slope, 0.04);
+        history.truncate(3);
+        history.push(DoseInrRecord {
+            inr: f64::INFINITY,
+            ..record(30.0, 1.2)
+        });
+        assert!(fit_dose_response(&history).is_err());
+    }
+}
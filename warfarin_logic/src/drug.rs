@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::FLOAT_TOLERANCE;
+
+// Every-other-day regimens are only offered for drugs whose half-life smooths
+// out the day-to-day difference.
+const MIN_HALF_LIFE_FOR_ALTERNATING_HOURS: f64 = 24.0;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum VkaDrug {
+    #[default]
+    Warfarin,
+    Acenocoumarol,
+    Phenprocoumon,
+}
+
+/// Drug-specific values used by the regimen generator and the planners.
+#[derive(Serialize, Debug)]
+pub struct VkaProfile {
+    pub drug: VkaDrug,
+    pub name: &'static str,
+    pub strengths_mg: &'static [u8], // descending
+    pub max_daily_dose_mg: f64,
+    pub dose_step_mg: f64, // smallest daily dose increment, half of the smallest tablet
+    pub half_life_hours: f64,
+    pub preop_stop_days: u32,     // days without a dose before a procedure
+    pub warfarin_dose_ratio: f64, // mean maintenance dose relative to warfarin
+}
+
+const PROFILES: [VkaProfile; 3] = [
+    VkaProfile {
+        drug: VkaDrug::Warfarin,
+        name: "วาร์ฟาริน",
+        strengths_mg: &[5, 3, 2, 1],
+        max_daily_dose_mg: 15.0,
+        dose_step_mg: 0.5,
+        half_life_hours: 40.0,
+        preop_stop_days: 5,
+        warfarin_dose_ratio: 1.0,
+    },
+    VkaProfile {
+        drug: VkaDrug::Acenocoumarol,
+        name: "อะซีโนคูมารอล",
+        strengths_mg: &[4, 1],
+        max_daily_dose_mg: 12.0,
+        dose_step_mg: 0.5,
+        half_life_hours: 10.0,
+        preop_stop_days: 3,
+        warfarin_dose_ratio: 0.5,
+    },
+    VkaProfile {
+        drug: VkaDrug::Phenprocoumon,
+        name: "เฟนโพรคูมอน",
+        strengths_mg: &[3],
+        max_daily_dose_mg: 9.0,
+        dose_step_mg: 1.5,
+        half_life_hours: 150.0,
+        preop_stop_days: 10,
+        warfarin_dose_ratio: 0.45,
+    },
+];
+
+pub fn vka_profiles() -> &'static [VkaProfile] {
+    &PROFILES
+}
+
+impl VkaDrug {
+    pub fn profile(self) -> &'static VkaProfile {
+        PROFILES
+            .iter()
+            .find(|profile| profile.drug == self)
+            .expect("every drug has a profile")
+    }
+}
+
+impl VkaProfile {
+    pub fn allows_alternating_days(&self) -> bool {
+        self.half_life_hours >= MIN_HALF_LIFE_FOR_ALTERNATING_HOURS
+    }
+
+    /// Every positive daily dose on the step grid up to the daily ceiling.
+    pub(crate) fn daily_dose_steps(&self) -> impl Iterator<Item = f64> + '_ {
+        let steps = (self.max_daily_dose_mg / self.dose_step_mg).round() as i32;
+        (1..=steps).map(|i| i as f64 * self.dose_step_mg)
+    }
+
+    pub(crate) fn is_dose_step(&self, dose: f64) -> bool {
+        let steps = dose / self.dose_step_mg;
+        (steps.round() - steps).abs() * self.dose_step_mg < FLOAT_TOLERANCE
+    }
+
+    pub(crate) fn check_strengths(&self, available_pills: &[u8]) -> Result<(), String> {
+        match available_pills
+            .iter()
+            .find(|mg| !self.strengths_mg.contains(mg))
+        {
+            Some(mg) => Err(format!("{}ไม่มีขนาด {} mg", self.name, mg)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn list_vka_profiles_rust() -> Result<JsValue, JsValue> {
+    Ok(serde_wasm_bindgen::to_value(vka_profiles())?)
+}
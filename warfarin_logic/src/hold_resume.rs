@@ -0,0 +1,270 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::date::CalendarDate;
+use crate::drug::VkaDrug;
+use crate::indication::InrTargetRequest;
+use crate::plan::{day_label, summarize_plan_pills, PlanPhase, PlannedDay};
+use crate::{
+    nearest_achievable_option, render_option, CalculationInput, FinalOutput, SpecialDayPattern,
+    TotalPillsSummary, FLOAT_TOLERANCE,
+};
+
+// How far (as a fraction of the current weekly dose) the reduced dose may drift
+// from the guideline reduction to land on a dose the selected tablets can build.
+const REDUCTION_SEARCH_WINDOW: f64 = 0.05;
+
+#[derive(Deserialize, Debug)]
+pub struct HoldResumeInput {
+    #[serde(default)]
+    pub drug: VkaDrug,
+    pub inr: f64,
+    pub current_weekly_dose: f64,
+    pub visit_date: CalendarDate,
+    pub days_until_appointment: u32,
+    pub allow_half: bool,
+    pub available_pills: Vec<u8>,
+    pub special_day_pattern: SpecialDayPattern,
+    #[serde(flatten)]
+    pub target: InrTargetRequest,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct VitaminKRecommendation {
+    pub min_dose_mg: f64,
+    pub max_dose_mg: f64,
+    pub optional: bool,
+    pub note: String,
+}
+
+#[derive(Serialize)]
+pub struct HoldResumePlan {
+    pub description: String,
+    pub hold_days: Vec<CalendarDate>,
+    pub vitamin_k: Option<VitaminKRecommendation>,
+    pub resume_date: CalendarDate,
+    pub weekly_dose_reduction_percent: f64,
+    pub reduced_regimen: FinalOutput,
+    pub days: Vec<PlannedDay>,
+    pub total_pills_summary: TotalPillsSummary,
+}
+
+/// Guideline action for an INR band (no bleeding).
+struct InrBand {
+    hold_days: u32,
+    reduction_percent: f64,
+    vitamin_k: Option<VitaminKRecommendation>,
+}
+
+fn inr_band(inr: f64) -> InrBand {
+    if inr < 4.0 {
+        InrBand {
+            hold_days: 0,
+            reduction_percent: 10.0,
+            vitamin_k: None,
+        }
+    } else if inr < 5.0 {
+        InrBand {
+            hold_days: 1,
+            reduction_percent: 10.0,
+            vitamin_k: None,
+        }
+    } else if inr < 9.0 {
+        InrBand {
+            hold_days: 2,
+            reduction_percent: 15.0,
+            vitamin_k: Some(VitaminKRecommendation {
+                min_dose_mg: 1.0,
+                max_dose_mg: 2.5,
+                optional: true,
+                note: "พิจารณาให้วิตามินเครับประทาน หากมีความเสี่ยงเลือดออกสูง".to_string(),
+            }),
+        }
+    } else {
+        InrBand {
+            hold_days: 2,
+            reduction_percent: 20.0,
+            vitamin_k: Some(VitaminKRecommendation {
+                min_dose_mg: 2.5,
+                max_dose_mg: 5.0,
+                optional: false,
+                note: "ให้วิตามินเครับประทาน และตรวจ INR ซ้ำภายใน 24–48 ชั่วโมง".to_string(),
+            }),
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn plan_hold_and_resume_rust(input_js: JsValue) -> Result<JsValue, JsValue> {
+    let input: HoldResumeInput =
+        serde_wasm_bindgen::from_value(input_js).map_err(|e| e.to_string())?;
+    let plan = plan_hold_and_resume(&input)?;
+    Ok(serde_wasm_bindgen::to_value(&plan)?)
+}
+
+/// Plans held doses from the visit date for an INR above range without bleeding,
+/// then resumes on the simplest regimen at the reduced weekly dose. Tablets are
+/// only counted for the days after the hold.
+pub fn plan_hold_and_resume(input: &HoldResumeInput) -> Result<HoldResumePlan, String> {
+    if !input.inr.is_finite() {
+        return Err("ค่า INR ไม่ถูกต้อง".to_string());
+    }
+    if !(input.current_weekly_dose.is_finite() && input.current_weekly_dose > 0.0) {
+        return Err("ขนาดยาเดิมต้องมากกว่า 0".to_string());
+    }
+    if input.inr <= input.target.resolve()?.high {
+        return Err("INR ไม่สูงกว่าช่วงเป้าหมาย".to_string());
+    }
+    let band = inr_band(input.inr);
+    let hold_days_count = band.hold_days.min(input.days_until_appointment);
+    let resume_date = input.visit_date.add_days(hold_days_count as i64);
+
+    let profile = input.drug.profile();
+    let mut available_pills = input.available_pills.clone();
+    available_pills.sort_unstable_by(|a, b| b.cmp(a));
+    profile.check_strengths(&available_pills)?;
+    let mut regimen_input = CalculationInput {
+        drug: input.drug,
+        weekly_dose: 0.0,
+        allow_half: input.allow_half,
+        available_pills,
+        special_day_pattern: input.special_day_pattern,
+        days_until_appointment: input.days_until_appointment - hold_days_count,
+        start_day_of_week: resume_date.day_of_week() as u8,
+        target: input.target,
+        ..Default::default()
+    };
+
+    // Try achievable doses closest to the guideline reduction first.
+    let reduced_target = input.current_weekly_dose * (1.0 - band.reduction_percent / 100.0);
+    let window = input.current_weekly_dose * REDUCTION_SEARCH_WINDOW;
+    let (option, reduced_weekly_dose) =
+        nearest_achievable_option(&regimen_input, reduced_target, window, |dose| {
+            dose < input.current_weekly_dose - FLOAT_TOLERANCE
+        })
+        .ok_or_else(|| "ไม่พบขนาดยาที่ลดลงซึ่งจัดได้ด้วยเม็ดยาที่เลือก".to_string())?;
+    regimen_input.weekly_dose = reduced_weekly_dose;
+    let reduced_regimen = render_option(&option, &regimen_input);
+
+    let mut days = Vec::new();
+    let mut hold_days = Vec::new();
+    for offset in 0..hold_days_count {
+        let date = input.visit_date.add_days(offset as i64);
+        hold_days.push(date);
+        days.push(PlannedDay::new(date, PlanPhase::Hold, &[]));
+    }
+    for (offset, combo) in option
+        .daily_combos(
+            regimen_input.start_day_of_week,
+            regimen_input.days_until_appointment,
+        )
+        .enumerate()
+    {
+        let date = resume_date.add_days(offset as i64);
+        days.push(PlannedDay::new(date, PlanPhase::NewRegimen, combo));
+    }
+
+    let weekly_dose_reduction_percent =
+        (1.0 - reduced_weekly_dose / input.current_weekly_dose) * 100.0;
+    let mut parts = Vec::new();
+    if !hold_days.is_empty() {
+        let labels: Vec<String> = hold_days.iter().map(|&d| day_label(d)).collect();
+        parts.push(format!(
+            "งดยา {} วัน ({})",
+            hold_days.len(),
+            labels.join(", ")
+        ));
+    }
+    parts.push(format!(
+        "เริ่มยาใหม่ {} ขนาด {:.1} mg/สัปดาห์ (ลดลง {:.0}%)",
+        day_label(resume_date),
+        reduced_weekly_dose,
+        weekly_dose_reduction_percent
+    ));
+    if let Some(vitamin_k) = &band.vitamin_k {
+        parts.push(format!(
+            "วิตามินเค {:.1}–{:.1} mg รับประทาน{}",
+            vitamin_k.min_dose_mg,
+            vitamin_k.max_dose_mg,
+            if vitamin_k.optional {
+                " (พิจารณา)"
+            } else {
+                ""
+            }
+        ));
+    }
+
+    let total_pills_summary = summarize_plan_pills(&days);
+    Ok(HoldResumePlan {
+        description: parts.join(", "),
+        hold_days,
+        vitamin_k: band.vitamin_k,
+        resume_date,
+        weekly_dose_reduction_percent,
+        reduced_regimen,
+        days,
+        total_pills_summary,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(inr: f64) -> HoldResumeInput {
+        HoldResumeInput {
+            drug: VkaDrug::default(),
+            inr,
+            current_weekly_dose: 35.0,
+            // Wednesday
+            visit_date: CalendarDate::from_ymd(2026, 10, 14).unwrap(),
+            days_until_appointment: 14,
+            allow_half: true,
+            available_pills: vec![2, 3, 5],
+            special_day_pattern: SpecialDayPattern::default(),
+            target: InrTargetRequest::default(),
+        }
+    }
+
+    #[test]
+    fn holds_two_days_and_resumes_reduced() {
+        let plan = plan_hold_and_resume(&input(6.0)).unwrap();
+        let visit = CalendarDate::from_ymd(2026, 10, 14).unwrap();
+        assert_eq!(plan.hold_days, vec![visit, visit.add_days(1)]);
+        assert_eq!(plan.resume_date, visit.add_days(2));
+        assert!(plan.vitamin_k.as_ref().unwrap().optional);
+        assert_eq!(plan.days.len(), 14);
+        assert!(plan.days[..2].iter().all(|d| d.phase == PlanPhase::Hold));
+        assert!(plan.days[2..]
+            .iter()
+            .all(|d| d.phase == PlanPhase::NewRegimen));
+        assert!(plan.weekly_dose_reduction_percent > 0.0);
+        assert!((plan.weekly_dose_reduction_percent - 15.0).abs() <= 5.0);
+    }
+
+    #[test]
+    fn slightly_high_inr_only_reduces() {
+        let plan = plan_hold_and_resume(&input(3.5)).unwrap();
+        assert!(plan.hold_days.is_empty());
+        assert!(plan.vitamin_k.is_none());
+        assert_eq!(plan.resume_date, input(3.5).visit_date);
+    }
+
+    #[test]
+    fn rejects_inr_within_range() {
+        assert!(plan_hold_and_resume(&input(2.8)).is_err());
+    }
+
+    #[test]
+    fn rejects_non_finite_inr_and_dose() {
+        assert!(plan_hold_and_resume(&input(f64::NAN)).is_err());
+        assert!(plan_hold_and_resume(&input(f64::INFINITY)).is_err());
+        for dose in [0.0, -5.0, f64::NAN, f64::INFINITY] {
+            let input = HoldResumeInput {
+                current_weekly_dose: dose,
+                ..input(6.0)
+            };
+            assert!(plan_hold_and_resume(&input).is_err());
+        }
+    }
+}
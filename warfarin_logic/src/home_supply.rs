@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+use crate::plan::regimen_from_schedule;
+use crate::DaySchedule;
+
+// Leftovers within this many tablets of the expected count are not flagged.
+const ADHERENCE_TOLERANCE_TABLETS: f64 = 1.0;
+
+/// Tablets of one strength the patient has at home, whole and already cut in half.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct HomeSupply {
+    pub mg: u8,
+    #[serde(default)]
+    pub whole: u32,
+    #[serde(default)]
+    pub halves: u32,
+}
+
+/// What the patient took home at the last visit and was asked to take since.
+#[derive(Deserialize, Debug, Clone)]
+pub struct PreviousDispensing {
+    pub regimen: Vec<DaySchedule>,
+    pub supply: Vec<HomeSupply>, // dispensed plus the leftovers kept then
+    pub days_elapsed: u32,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SupplyMismatch {
+    pub mg: u8,
+    pub expected_tablets: f64,
+    pub on_hand_tablets: f64,
+    pub message: String,
+}
+
+/// Compares the leftovers with what the previous regimen should have left, one
+/// entry per strength that is off by more than a tablet. The previous period is
+/// assumed to end the day before `start_day_of_week` and to start in week A.
+pub(crate) fn check_home_supply(
+    previous: &PreviousDispensing,
+    on_hand: &[HomeSupply],
+    start_day_of_week: u8,
+) -> Result<Vec<SupplyMismatch>, String> {
+    let cycle = regimen_from_schedule(&previous.regimen)?;
+    let first_day = (start_day_of_week as usize + 7 - previous.days_elapsed as usize % 7) % 7;
+
+    let mut strengths: Vec<u8> = previous
+        .supply
+        .iter()
+        .chain(on_hand)
+        .map(|s| s.mg)
+        .chain(cycle.iter().flatten().map(|p| p.mg))
+        .collect();
+    strengths.sort_unstable_by(|a, b| b.cmp(a));
+    strengths.dedup();
+
+    let halves_of = |supply: &[HomeSupply], mg: u8| {
+        supply
+            .iter()
+            .filter(|s| s.mg == mg)
+            .map(|s| s.whole as i64 * 2 + s.halves as i64)
+            .sum::<i64>()
+    };
+    let mut mismatches = Vec::new();
+    for mg in strengths {
+        let taken_halves: i64 = (0..previous.days_elapsed as usize)
+            .flat_map(|day| &cycle[(first_day + day) % cycle.len()])
+            .filter(|p| p.mg == mg)
+            .map(|p| p.count as i64 * if p.half { 1 } else { 2 })
+            .sum();
+        let expected_tablets = (halves_of(&previous.supply, mg) - taken_halves).max(0) as f64 / 2.0;
+        let on_hand_tablets = halves_of(on_hand, mg) as f64 / 2.0;
+        let difference = on_hand_tablets - expected_tablets;
+        if difference.abs() <= ADHERENCE_TOLERANCE_TABLETS {
+            continue;
+        }
+        let message = if difference > 0.0 {
+            format!(
+                "ยา {} mg เหลือมากกว่าที่ควร {:.1} เม็ด อาจลืมรับประทานยา",
+                mg, difference
+            )
+        } else {
+            format!(
+                "ยา {} mg เหลือน้อยกว่าที่ควร {:.1} เม็ด อาจรับประทานเกินหรือยาสูญหาย",
+                mg, -difference
+            )
+        };
+        mismatches.push(SupplyMismatch {
+            mg,
+            expected_tablets,
+            on_hand_tablets,
+            message,
+        });
+    }
+    Ok(mismatches)
+}
@@ -0,0 +1,279 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+// Used when neither an indication nor a range is given.
+const DEFAULT_INR_TARGET: InrTarget = InrTarget {
+    low: 2.0,
+    high: 3.0,
+};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Indication {
+    AtrialFibrillation,
+    ProvokedVenousThromboembolism,
+    UnprovokedVenousThromboembolism,
+    MechanicalAorticValve,
+    MechanicalMitralValve,
+    MechanicalValveHighRisk, // aortic valve with risk factors, older valve designs or more than one valve
+    BioprostheticValve,
+    LeftVentricularThrombus,
+    AntiphospholipidSyndrome,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct InrTarget {
+    pub low: f64,
+    pub high: f64,
+}
+
+/// One row of the indication catalog.
+#[derive(Serialize, Debug)]
+pub struct IndicationInfo {
+    pub indication: Indication,
+    pub name: &'static str,
+    pub target: InrTarget,
+    pub duration_months: Option<u32>, // None for long-term treatment
+    pub duration: &'static str,
+}
+
+const CATALOG: [IndicationInfo; 9] = [
+    IndicationInfo {
+        indication: Indication::AtrialFibrillation,
+        name: "หัวใจห้องบนเต้นผิดจังหวะ (AF)",
+        target: InrTarget {
+            low: 2.0,
+            high: 3.0,
+        },
+        duration_months: None,
+        duration: "ระยะยาว",
+    },
+    IndicationInfo {
+        indication: Indication::ProvokedVenousThromboembolism,
+        name: "ลิ่มเลือดอุดตันในหลอดเลือดดำ (VTE) ที่มีปัจจัยกระตุ้น",
+        target: InrTarget {
+            low: 2.0,
+            high: 3.0,
+        },
+        duration_months: Some(3),
+        duration: "3 เดือน",
+    },
+    IndicationInfo {
+        indication: Indication::UnprovokedVenousThromboembolism,
+        name: "ลิ่มเลือดอุดตันในหลอดเลือดดำ (VTE) ที่ไม่มีปัจจัยกระตุ้น",
+        target: InrTarget {
+            low: 2.0,
+            high: 3.0,
+        },
+        duration_months: None,
+        duration: "อย่างน้อย 3 เดือน แล้วพิจารณาใช้ต่อระยะยาว",
+    },
+    IndicationInfo {
+        indication: Indication::MechanicalAorticValve,
+        name: "ลิ้นหัวใจเอออร์ติกเทียมชนิดโลหะ",
+        target: InrTarget {
+            low: 2.0,
+            high: 3.0,
+        },
+        duration_months: None,
+        duration: "ตลอดชีวิต",
+    },
+    IndicationInfo {
+        indication: Indication::MechanicalMitralValve,
+        name: "ลิ้นหัวใจไมทรัลเทียมชนิดโลหะ",
+        target: InrTarget {
+            low: 2.5,
+            high: 3.5,
+        },
+        duration_months: None,
+        duration: "ตลอดชีวิต",
+    },
+    IndicationInfo {
+        indication: Indication::MechanicalValveHighRisk,
+        name: "ลิ้นหัวใจเทียมชนิดโลหะที่มีความเสี่ยงสูง",
+        target: InrTarget {
+            low: 2.5,
+            high: 3.5,
+        },
+        duration_months: None,
+        duration: "ตลอดชีวิต",
+    },
+    IndicationInfo {
+        indication: Indication::BioprostheticValve,
+        name: "ลิ้นหัวใจเทียมชนิดเนื้อเยื่อ",
+        target: InrTarget {
+            low: 2.0,
+            high: 3.0,
+        },
+        duration_months: Some(3),
+        duration: "3 เดือนหลังผ่าตัด",
+    },
+    IndicationInfo {
+        indication: Indication::LeftVentricularThrombus,
+        name: "ลิ่มเลือดในหัวใจห้องล่างซ้าย",
+        target: InrTarget {
+            low: 2.0,
+            high: 3.0,
+        },
+        duration_months: Some(3),
+        duration: "3 เดือน แล้วประเมินซ้ำ",
+    },
+    IndicationInfo {
+        indication: Indication::AntiphospholipidSyndrome,
+        name: "กลุ่มอาการแอนติฟอสโฟลิพิด (APS)",
+        target: InrTarget {
+            low: 2.0,
+            high: 3.0,
+        },
+        duration_months: None,
+        duration: "ระยะยาว",
+    },
+];
+
+/// Target range for a patient: the indication's default range, with either bound
+/// overridden when given. Flattened into the inputs of the INR-related features.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default)]
+#[serde(default)]
+pub struct InrTargetRequest {
+    pub indication: Option<Indication>,
+    pub target_inr_low: Option<f64>,
+    pub target_inr_high: Option<f64>,
+}
+
+pub fn indication_catalog() -> &'static [IndicationInfo] {
+    &CATALOG
+}
+
+impl Indication {
+    pub fn info(self) -> &'static IndicationInfo {
+        CATALOG
+            .iter()
+            .find(|info| info.indication == self)
+            .expect("every indication is in the catalog")
+    }
+}
+
+impl InrTargetRequest {
+    pub fn is_empty(&self) -> bool {
+        self.indication.is_none() && self.target_inr_low.is_none() && self.target_inr_high.is_none()
+    }
+
+    pub fn resolve(&self) -> Result<InrTarget, String> {
+        let base = self
+            .indication
+            .map_or(DEFAULT_INR_TARGET, |indication| indication.info().target);
+        let target = InrTarget {
+            low: self.target_inr_low.unwrap_or(base.low),
+            high: self.target_inr_high.unwrap_or(base.high),
+        };
+        if !(target.low.is_finite() && target.high.is_finite())
+            || target.low <= 0.0
+            || target.low >= target.high
+        {
+            return Err(format!(
+                "ช่วง INR เป้าหมายไม่ถูกต้อง: {:.1}–{:.1}",
+                target.low, target.high
+            ));
+        }
+        Ok(target)
+    }
+}
+
+#[wasm_bindgen]
+pub fn list_indications_rust() -> Result<JsValue, JsValue> {
+    Ok(serde_wasm_bindgen::to_value(indication_catalog())?)
+}
+
+#[wasm_bindgen]
+pub fn resolve_inr_target_rust(input_js: JsValue) -> Result<JsValue, JsValue> {
+    let request: InrTargetRequest =
+        serde_wasm_bindgen::from_value(input_js).map_err(|e| e.to_string())?;
+    Ok(serde_wasm_bindgen::to_value(&request.resolve()?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(indication: Option<Indication>) -> InrTargetRequest {
+        InrTargetRequest {
+            indication,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn resolves_each_indication_to_its_range() {
+        let expected = [
+            (Indication::AtrialFibrillation, 2.0, 3.0),
+            (Indication::ProvokedVenousThromboembolism, 2.0, 3.0),
+            (Indication::UnprovokedVenousThromboembolism, 2.0, 3.0),
+            (Indication::MechanicalAorticValve, 2.0, 3.0),
+            (Indication::MechanicalMitralValve, 2.5, 3.5),
+            (Indication::MechanicalValveHighRisk, 2.5, 3.5),
+            (Indication::BioprostheticValve, 2.0, 3.0),
+            (Indication::LeftVentricularThrombus, 2.0, 3.0),
+            (Indication::AntiphospholipidSyndrome, 2.0, 3.0),
+        ];
+        assert_eq!(expected.len(), indication_catalog().len());
+        for (indication, low, high) in expected {
+            assert_eq!(
+                request(Some(indication)).resolve(),
+                Ok(InrTarget { low, high }),
+                "{:?}",
+                indication
+            );
+        }
+    }
+
+    #[test]
+    fn defaults_to_2_to_3_without_an_indication() {
+        assert!(request(None).is_empty());
+        assert_eq!(request(None).resolve(), Ok(DEFAULT_INR_TARGET));
+    }
+
+    #[test]
+    fn overrides_either_bound() {
+        let target = InrTargetRequest {
+            target_inr_high: Some(4.0),
+            ..request(Some(Indication::MechanicalMitralValve))
+        };
+        assert!(!target.is_empty());
+        assert_eq!(
+            target.resolve(),
+            Ok(InrTarget {
+                low: 2.5,
+                high: 4.0
+            })
+        );
+        let target = InrTargetRequest {
+            target_inr_low: Some(1.5),
+            ..request(None)
+        };
+        assert_eq!(
+            target.resolve(),
+            Ok(InrTarget {
+                low: 1.5,
+                high: 3.0
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_an_invalid_range() {
+        for (low, high) in [
+            (3.0, 2.0),
+            (2.0, 2.0),
+            (0.0, 3.0),
+            (f64::NAN, 3.0),
+            (2.0, f64::INFINITY),
+        ] {
+            let target = InrTargetRequest {
+                target_inr_low: Some(low),
+                target_inr_high: Some(high),
+                ..request(None)
+            };
+            assert!(target.resolve().is_err(), "{}–{}", low, high);
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::drug::VkaDrug;
+
+const EMBEDDED_TABLE: &str = include_str!("../data/interactions.json");
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum InteractionEffect {
+    IncreasesInr,
+    DecreasesInr,
+}
+
+/// One row of the interaction table, in the format of `data/interactions.json`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DrugInteraction {
+    pub drug: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub effect: InteractionEffect,
+    pub dose_change_percent: f64, // empirical warfarin dose change when starting the drug
+    pub onset_days: u32,
+    pub monitoring: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct InteractionTable {
+    entries: Vec<DrugInteraction>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum MedicationAction {
+    Start,
+    Stop,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CoMedicationChange {
+    pub drug: String,
+    pub action: MedicationAction,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct InteractionInput {
+    #[serde(default)]
+    pub drug: VkaDrug,
+    pub weekly_dose: f64,
+    pub changes: Vec<CoMedicationChange>,
+    pub interaction_table: Option<Vec<DrugInteraction>>, // replaces the embedded table
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct InteractionWarning {
+    pub drug: String,
+    pub action: MedicationAction,
+    pub effect: InteractionEffect,
+    pub dose_change_percent: f64,
+    pub onset_days: u32,
+    pub monitoring: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct InteractionAssessment {
+    pub warnings: Vec<InteractionWarning>,
+    pub unknown_drugs: Vec<String>,
+    pub total_dose_change_percent: f64,
+    pub suggested_weekly_dose: f64, // rounded to the drug's dose step, ready for `CalculationInput`
+}
+
+impl InteractionTable {
+    /// The table shipped with the crate.
+    pub fn embedded() -> Self {
+        InteractionTable::from_json(EMBEDDED_TABLE).expect("embedded interaction table is valid")
+    }
+
+    /// Loads a table maintained outside the crate, in the same format as the embedded one.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let entries: Vec<DrugInteraction> =
+            serde_json::from_str(json).map_err(|e| format!("ตารางปฏิกิริยาระหว่างยาไม่ถูกต้อง: {}", e))?;
+        InteractionTable::try_from(entries)
+    }
+
+    pub fn entries(&self) -> &[DrugInteraction] {
+        &self.entries
+    }
+
+    /// Case-insensitive lookup by generic name or alias.
+    pub fn find(&self, drug: &str) -> Option<&DrugInteraction> {
+        let name = drug.trim().to_lowercase();
+        self.entries.iter().find(|entry| {
+            entry.drug.to_lowercase() == name
+                || entry
+                    .aliases
+                    .iter()
+                    .any(|alias| alias.to_lowercase() == name)
+        })
+    }
+}
+
+impl TryFrom<Vec<DrugInteraction>> for InteractionTable {
+    type Error = String;
+
+    /// Rejects a dose change of -100% or less, which stopping the drug could not undo.
+    fn try_from(entries: Vec<DrugInteraction>) -> Result<Self, String> {
+        if let Some(entry) = entries
+            .iter()
+            .find(|e| !e.dose_change_percent.is_finite() || e.dose_change_percent <= -100.0)
+        {
+            return Err(format!(
+                "ตารางปฏิกิริยาระหว่างยาไม่ถูกต้อง: dose_change_percent ของ {} ต้องมากกว่า -100",
+                entry.drug
+            ));
+        }
+        Ok(InteractionTable { entries })
+    }
+}
+
+#[wasm_bindgen]
+pub fn assess_interactions_rust(input_js: JsValue) -> Result<JsValue, JsValue> {
+    let input: InteractionInput =
+        serde_wasm_bindgen::from_value(input_js).map_err(|e| e.to_string())?;
+    let table = match input.interaction_table {
+        Some(entries) => InteractionTable::try_from(entries)?,
+        None => InteractionTable::embedded(),
+    };
+    let assessment = assess_interactions(&table, input.drug, input.weekly_dose, &input.changes);
+    Ok(serde_wasm_bindgen::to_value(&assessment)?)
+}
+
+/// Warns about each co-medication being started or stopped and compounds their
+/// empirical dose changes into a suggested weekly dose. Stopping a drug undoes the
+/// change its start would have made.
+pub fn assess_interactions(
+    table: &InteractionTable,
+    drug: VkaDrug,
+    weekly_dose: f64,
+    changes: &[CoMedicationChange],
+) -> InteractionAssessment {
+    let mut warnings = Vec::new();
+    let mut unknown_drugs = Vec::new();
+    let mut dose_factor = 1.0;
+    let step = drug.profile().dose_step_mg;
+
+    for change in changes {
+        let Some(entry) = table.find(&change.drug) else {
+            unknown_drugs.push(change.drug.clone());
+            continue;
+        };
+        let start_factor = 1.0 + entry.dose_change_percent / 100.0;
+        let factor = match change.action {
+            MedicationAction::Start => start_factor,
+            MedicationAction::Stop => 1.0 / start_factor,
+        };
+        dose_factor *= factor;
+
+        let (effect, direction) = match (entry.effect, change.action) {
+            (InteractionEffect::IncreasesInr, MedicationAction::Start)
+            | (InteractionEffect::DecreasesInr, MedicationAction::Stop) => {
+                (InteractionEffect::IncreasesInr, "เพิ่ม INR")
+            }
+            _ => (InteractionEffect::DecreasesInr, "ลด INR"),
+        };
+        let action = match change.action {
+            MedicationAction::Start => "เริ่ม",
+            MedicationAction::Stop => "หยุด",
+        };
+        let dose_change_percent = (factor - 1.0) * 100.0;
+        warnings.push(InteractionWarning {
+            drug: entry.drug.clone(),
+            action: change.action,
+            effect,
+            dose_change_percent,
+            onset_days: entry.onset_days,
+            monitoring: entry.monitoring.clone(),
+            message: format!(
+                "{} {}: {} คาดว่าต้องปรับขนาดยา {:+.0}% เห็นผลในราว {} วัน",
+                action, entry.drug, direction, dose_change_percent, entry.onset_days
+            ),
+        });
+    }
+
+    InteractionAssessment {
+        warnings,
+        unknown_drugs,
+        total_dose_change_percent: (dose_factor - 1.0) * 100.0,
+        suggested_weekly_dose: (weekly_dose * dose_factor / step).round() * step,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(dose_change_percent: f64) -> DrugInteraction {
+        DrugInteraction {
+            drug: "testdrug".to_string(),
+            aliases: Vec::new(),
+            effect: InteractionEffect::IncreasesInr,
+            dose_change_percent,
+            onset_days: 3,
+            monitoring: String::new(),
+        }
+    }
+
+    #[test]
+    fn rejects_dose_changes_stopping_cannot_undo() {
+        for percent in [-100.0, -150.0, f64::NAN, f64::INFINITY] {
+            assert!(InteractionTable::try_from(vec![entry(percent)]).is_err());
+        }
+        assert!(InteractionTable::try_from(vec![entry(-50.0)]).is_ok());
+        assert!(InteractionTable::from_json(
+            r#"[{"drug": "x", "effect": "increases-inr", "dose_change_percent": -100,
+                "onset_days": 3, "monitoring": ""}]"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn rounds_the_suggested_dose_to_the_drug_step() {
+        let table = InteractionTable::try_from(vec![entry(-30.0)]).unwrap();
+        let start = [CoMedicationChange {
+            drug: "TestDrug".to_string(),
+            action: MedicationAction::Start,
+        }];
+        // 35 * 0.7 = 24.5
+        let warfarin = assess_interactions(&table, VkaDrug::Warfarin, 35.0, &start);
+        assert_eq!(warfarin.suggested_weekly_dose, 24.5);
+        let phenprocoumon = assess_interactions(&table, VkaDrug::Phenprocoumon, 35.0, &start);
+        assert_eq!(phenprocoumon.suggested_weekly_dose, 24.0);
+    }
+}
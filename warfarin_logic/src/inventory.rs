@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+use crate::PillLineSummary;
+
+/// Tablets of one strength on the pharmacy shelf. Strengths without an entry are
+/// treated as unlimited.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PillStock {
+    pub mg: u8,
+    pub quantity: u32,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct StockShortfall {
+    pub mg: u8,
+    pub needed: u32,
+    pub available: u32,
+}
+
+/// Stock of one strength left after dispensing an option.
+#[derive(Serialize, Debug, Clone)]
+pub struct StockLine {
+    pub mg: u8,
+    pub remaining: u32,
+}
+
+/// A regimen the shelf can fill, offered with one whose strengths run short.
+#[derive(Serialize, Debug, Clone)]
+pub struct StockAlternative {
+    pub short_strengths: Vec<u8>,
+    pub description: String,
+    pub message: String,
+}
+
+pub(crate) fn stock_shortfalls(
+    pill_lines: &[PillLineSummary],
+    stock: &[PillStock],
+) -> Vec<StockShortfall> {
+    stock
+        .iter()
+        .filter_map(|s| {
+            let needed = dispensed(pill_lines, s.mg);
+            (needed > s.quantity).then_some(StockShortfall {
+                mg: s.mg,
+                needed,
+                available: s.quantity,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn remaining_stock(
+    pill_lines: &[PillLineSummary],
+    stock: &[PillStock],
+) -> Vec<StockLine> {
+    stock
+        .iter()
+        .map(|s| StockLine {
+            mg: s.mg,
+            remaining: s.quantity.saturating_sub(dispensed(pill_lines, s.mg)),
+        })
+        .collect()
+}
+
+/// Names `description`, the best regimen the shelf can fill, as the split to use
+/// instead when `shortfalls` is not empty.
+pub(crate) fn stock_alternative(
+    shortfalls: &[StockShortfall],
+    description: Option<&str>,
+) -> Option<StockAlternative> {
+    let description = description.filter(|_| !shortfalls.is_empty())?;
+    let short_strengths: Vec<u8> = shortfalls.iter().map(|s| s.mg).collect();
+    let labels: Vec<String> = short_strengths
+        .iter()
+        .map(|mg| format!("{} mg", mg))
+        .collect();
+    Some(StockAlternative {
+        message: format!(
+            "ยา {} ในคลังไม่พอ ใช้ {} แทนได้",
+            labels.join(", "),
+            description
+        ),
+        short_strengths,
+        description: description.to_string(),
+    })
+}
+
+// A strength can appear on more than one line, e.g. once more for buffer days.
+fn dispensed(pill_lines: &[PillLineSummary], mg: u8) -> u32 {
+    pill_lines
+        .iter()
+        .filter(|line| line.mg == mg)
+        .map(|line| line.dispensed_count)
+        .sum()
+}
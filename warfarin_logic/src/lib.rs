@@ -2,26 +2,184 @@ use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use wasm_bindgen::prelude::*;
 
+use crate::buffer::buffer_summary;
+use crate::diagnostics::Rejections;
+use crate::home_supply::check_home_supply;
+use crate::inventory::{remaining_stock, stock_alternative, stock_shortfalls};
+use crate::packs::{apply_pack_rounding, check_packs};
+use crate::plan::regimen_from_schedule;
+use crate::pricing::{check_prices, regimen_cost, weekly_cost};
+use crate::regimen_change::regimen_change;
+use crate::safety::{check_predicted_inr, check_safety};
+
+mod buffer;
+mod date;
+mod diagnostics;
+mod dose_comparison;
+mod dose_ladder;
+mod dose_response;
+mod drug;
+mod hold_resume;
+mod home_supply;
+mod indication;
+mod interactions;
+mod inventory;
+mod next_inr_check;
+mod packs;
+mod perioperative;
+mod plan;
+mod pricing;
+mod regimen_change;
+mod regimen_text;
+mod risk_scores;
+mod safety;
+mod snap;
+mod switching;
+mod table;
+mod transition;
+mod ttr;
+
+pub use buffer::{BufferSummary, SupplyBuffer};
+pub use date::CalendarDate;
+pub use diagnostics::{
+    diagnose_suggestions, diagnose_suggestions_rust, RejectionReason, RejectionSummary, Remedy,
+    RemedyAction, SuggestionDiagnostics,
+};
+pub use dose_comparison::{
+    compare_dose_adjustments, compare_dose_adjustments_rust, DoseChange, DoseComparisonInput,
+    DoseGroup,
+};
+pub use dose_ladder::{
+    generate_dose_ladder, generate_dose_ladder_rust, DoseLadder, DoseLadderInput, LadderRow,
+};
+pub use dose_response::{
+    fit_dose_response, fit_dose_response_rust, DoseInrRecord, DoseResponseModel, InrPrediction,
+};
+pub use drug::{list_vka_profiles_rust, vka_profiles, VkaDrug, VkaProfile};
+pub use hold_resume::{
+    plan_hold_and_resume, plan_hold_and_resume_rust, HoldResumeInput, HoldResumePlan,
+    VitaminKRecommendation,
+};
+pub use home_supply::{HomeSupply, PreviousDispensing, SupplyMismatch};
+pub use indication::{
+    indication_catalog, list_indications_rust, resolve_inr_target_rust, Indication, IndicationInfo,
+    InrTarget, InrTargetRequest,
+};
+pub use interactions::{
+    assess_interactions, assess_interactions_rust, CoMedicationChange, DrugInteraction,
+    InteractionAssessment, InteractionEffect, InteractionInput, InteractionTable,
+    InteractionWarning, MedicationAction,
+};
+pub use inventory::{PillStock, StockAlternative, StockLine, StockShortfall};
+pub use next_inr_check::{
+    recommend_next_inr_check, recommend_next_inr_check_rust, CheckReason, NextInrCheck,
+    NextInrCheckInput,
+};
+pub use packs::{PackCount, PackSize, RoundingPolicy};
+pub use perioperative::{
+    plan_perioperative, plan_perioperative_rust, BleedingRisk, DoseTime, LmwhDose,
+    PerioperativeInput, PerioperativePlan, ThromboembolicRisk,
+};
+pub use plan::{PlanPhase, PlannedDay};
+pub use pricing::{RegimenCost, TabletPrice};
+pub use regimen_change::{ChangedDay, RegimenChange};
+pub use regimen_text::{
+    parse_regimen, parse_regimen_rust, ParseRegimenInput, ParsedRegimen, RegimenComplexity,
+};
+pub use risk_scores::{
+    calculate_risk_scores, calculate_risk_scores_rust, score_atria, score_cha2ds2_vasc,
+    score_has_bled, AtriaInput, Cha2ds2VascInput, HasBledInput, RiskCategory, RiskScore,
+    RiskScoreInput, RiskScores, Sex,
+};
+pub use safety::{SafetyLimits, SafetyRule, SafetyWarning, Severity};
+pub use snap::{snap_weekly_dose, snap_weekly_dose_rust, AchievableDose, SnappedDose};
+pub use switching::{
+    plan_anticoagulant_switch, plan_anticoagulant_switch_rust, Doac, InrThreshold, SwitchInput,
+    SwitchPlan, SwitchTo,
+};
+pub use table::TableFormat;
+pub use transition::{
+    plan_dose_transition, plan_dose_transition_rust, DoseAdjustment, TransitionInput,
+    TransitionPlan, TransitionStart,
+};
+pub use ttr::{calculate_ttr, calculate_ttr_rust, InrResult, TestGap, TtrInput, TtrResult};
+
 const FLOAT_TOLERANCE: f64 = 0.01;
-const ABSOLUTE_MAX_DAILY_DOSE: f64 = 15.0;
 const DOSE_MULTIPLIER_LIMIT: f64 = 2.5;
-
-#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
-enum SpecialDayPattern {
+const CYCLE_WEEKS: usize = 2;
+const MAX_CYCLE_WEEK_DEVIATION: f64 = 0.25;
+const CYCLE_WEEK_CANDIDATES: usize = 3;
+const DEFAULT_MAX_TABLETS_PER_DAY: u8 = 4;
+const MAX_TABLETS_PER_DAY_LIMIT: u8 = 6;
+const DAY_NAMES: [&str; 7] = ["จ.", "อ.", "พ.", "พฤ.", "ศ.", "ส.", "อา."];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpecialDayPattern {
+    #[default]
     #[serde(rename = "fri-sun")]
     FriSun,
     #[serde(rename = "mon-wed-fri")]
     MonWedFri,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum RankBy {
+    #[default]
+    Simplicity,
+    Cost,
+    MinimalChange, // fewest differences from `current_regimen`
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
 pub struct CalculationInput {
+    #[serde(default)]
+    pub drug: VkaDrug,
     pub weekly_dose: f64,
     pub allow_half: bool,
     pub available_pills: Vec<u8>,
     pub special_day_pattern: SpecialDayPattern,
     pub days_until_appointment: u32,
     pub start_day_of_week: u8, // 0=Mon, 1=Tue, ..., 6=Sun
+    #[serde(default)]
+    pub inr_history: Vec<DoseInrRecord>,
+    #[serde(default)]
+    pub previous_weekly_dose: Option<f64>,
+    #[serde(default)]
+    pub safety: SafetyLimits,
+    #[serde(default)]
+    pub bleeding_risk: Option<RiskCategory>, // e.g. from HAS-BLED; tightens `safety`
+    #[serde(flatten)]
+    pub target: InrTargetRequest,
+    #[serde(default)]
+    pub stock: Vec<PillStock>,
+    #[serde(default)]
+    pub exclude_out_of_stock: bool,
+    #[serde(default)]
+    pub home_supply: Vec<HomeSupply>, // leftovers the patient brought back
+    #[serde(default)]
+    pub previous_dispensing: Option<PreviousDispensing>,
+    #[serde(default)]
+    pub packs: Vec<PackSize>,
+    #[serde(default)]
+    pub rounding: RoundingPolicy,
+    #[serde(default)]
+    pub buffer: SupplyBuffer,
+    #[serde(default)]
+    pub prices: Vec<TabletPrice>,
+    #[serde(default)]
+    pub rank_by: RankBy,
+    #[serde(default)]
+    pub current_regimen: Vec<DaySchedule>, // what the patient takes now, if known
+    #[serde(default)]
+    pub max_tablets_per_day: Option<u8>, // tablet objects per day, 4 by default
+}
+
+impl CalculationInput {
+    fn max_tablets(&self) -> u8 {
+        self.max_tablets_per_day
+            .unwrap_or(DEFAULT_MAX_TABLETS_PER_DAY)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -34,7 +192,14 @@ pub struct Pill {
 #[derive(Debug, Clone)]
 enum OptionType {
     Uniform(Vec<Pill>),
+    // One entry per day of a cycle of 7 * N days, starting on Monday of week A.
     NonUniform(Vec<Vec<Pill>>),
+    // Every-other-day doses by calendar-day parity: `first` falls on the start date.
+    Alternating {
+        first: Vec<Pill>,
+        second: Vec<Pill>,
+        start_day_of_week: usize,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -47,10 +212,11 @@ struct DosageOption {
     stop_days: Vec<usize>,
     num_special_days: u8,
     special_days: Vec<usize>,
-    priority: u8, // 0 for uniform, 1 for non-uniform
+    priority: u8, // 0 uniform, 1 one-week non-uniform, 2 alternating, 3 multi-week cycle
     half_pill_complexity: usize,
     pill_color_count: usize,
     total_pill_objects: u32,
+    week_options: Vec<DosageOption>, // per-week options of a multi-week cycle
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -60,10 +226,33 @@ pub struct PillRenderData {
     pub is_half: bool,
 }
 
+impl From<&Pill> for PillRenderData {
+    fn from(p: &Pill) -> Self {
+        PillRenderData {
+            mg: p.mg,
+            count: p.count,
+            is_half: p.half,
+        }
+    }
+}
+
+impl From<&PillRenderData> for Pill {
+    fn from(p: &PillRenderData) -> Self {
+        Pill {
+            mg: p.mg,
+            count: p.count,
+            half: p.is_half,
+        }
+    }
+}
+
 #[derive(Serialize, Clone, Debug)]
 pub struct PillLineSummary {
     pub mg: u8,
-    pub dispensed_count: u32,
+    pub required_count: u32,
+    pub dispensed_count: u32, // net of the patient's home supply, rounded to `packs`
+    pub surplus_count: u32,   // dispensed beyond the need because of rounding
+    pub packs: Option<PackCount>,
     pub usage_note: String,
 }
 
@@ -71,11 +260,15 @@ pub struct PillLineSummary {
 pub struct TotalPillsSummary {
     pub header: String,
     pub pill_lines: Vec<PillLineSummary>,
+    pub buffer: Option<BufferSummary>, // dispensed on top of `pill_lines`
+    pub supply_mismatches: Vec<SupplyMismatch>, // possible non-adherence to ask about
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct DaySchedule {
     pub day_index: usize,
+    #[serde(default)]
+    pub week_index: usize,
     pub total_dose: f64,
     pub pills: Vec<PillRenderData>,
     pub is_stop_day: bool,
@@ -86,24 +279,166 @@ pub struct DaySchedule {
 pub struct FinalOutput {
     pub description: String,
     pub weekly_dose_actual: f64,
+    pub cycle_weeks: usize,
     pub weekly_schedule: Vec<DaySchedule>,
     pub total_pills_summary: TotalPillsSummary,
+    pub predicted_inr: Option<InrPrediction>, // None without enough INR history
+    pub warnings: Vec<SafetyWarning>,
+    pub stock_shortfalls: Vec<StockShortfall>,
+    pub stock_alternative: Option<StockAlternative>, // with shortfalls, a split the shelf can fill
+    pub remaining_stock: Vec<StockLine>,             // empty when no stock is given
+    pub cost: Option<RegimenCost>,                   // None unless every strength used has a price
+    pub change: Option<RegimenChange>,               // None without `current_regimen`
 }
 
 #[wasm_bindgen]
 pub fn generate_suggestions_rust(input_js: JsValue) -> Result<JsValue, JsValue> {
     let mut input: CalculationInput =
         serde_wasm_bindgen::from_value(input_js).map_err(|e| e.to_string())?;
+    prepare_calculation_input(&mut input)?;
+    Ok(serde_wasm_bindgen::to_value(&generate_suggestions(&input))?)
+}
 
+/// Sorts `available_pills` and rejects the inputs `generate_suggestions` expects
+/// to be valid.
+pub(crate) fn prepare_calculation_input(input: &mut CalculationInput) -> Result<(), String> {
     input.available_pills.sort_unstable_by(|a, b| b.cmp(a));
+    input
+        .drug
+        .profile()
+        .check_strengths(&input.available_pills)?;
+    input.target.resolve()?;
+    if let Some(previous) = &input.previous_dispensing {
+        check_home_supply(previous, &input.home_supply, input.start_day_of_week)?;
+    }
+    check_prices(&input.prices)?;
+    check_packs(&input.packs)?;
+    if !(1..=MAX_TABLETS_PER_DAY_LIMIT).contains(&input.max_tablets()) {
+        return Err(format!(
+            "จำนวนเม็ดยาต่อวันต้องอยู่ระหว่าง 1 ถึง {} เม็ด",
+            MAX_TABLETS_PER_DAY_LIMIT
+        ));
+    }
+    if !input.current_regimen.is_empty() {
+        regimen_from_schedule(&input.current_regimen)?;
+    } else if input.rank_by == RankBy::MinimalChange {
+        return Err("การเรียงตามการเปลี่ยนแปลงน้อยที่สุดต้องระบุตารางยาปัจจุบัน".to_string());
+    }
+    Ok(())
+}
 
+/// Renders the top options for Vue. Expects `available_pills` sorted descending,
+/// strengths of `drug`, a valid `target` and `previous_dispensing`. With `stock`, options the shelf can
+/// fill rank first, so a split across other strengths replaces one that runs low,
+/// and an option that runs low names the best of them as its alternative.
+/// `rank_by` can order the options by weekly tablet cost or by how little they
+/// change `current_regimen` before that.
+pub fn generate_suggestions(input: &CalculationInput) -> Vec<FinalOutput> {
+    let dose_response = fit_dose_response(&input.inr_history).ok();
+    let target = input.target.resolve().ok();
+    let mut options = generate_options(input);
+    if input.rank_by == RankBy::Cost {
+        // Unpriced options keep their simplicity order after the priced ones.
+        options.sort_by_cached_key(|opt| {
+            weekly_cost(opt, &input.prices)
+                .map_or((1, 0), |cost| (0, (cost * 100.0).round() as i64))
+        });
+    }
+    if input.rank_by == RankBy::MinimalChange {
+        if let Ok(current) = regimen_from_schedule(&input.current_regimen) {
+            options.sort_by_cached_key(|opt| regimen_change(opt, &current).distance);
+        }
+    }
+    let mut alternative = None;
+    if !input.stock.is_empty() {
+        let mut ranked: Vec<(bool, DosageOption)> = options
+            .into_iter()
+            .map(|opt| (!stock_shortfalls_for(&opt, input).is_empty(), opt))
+            .filter(|(short, _)| !(*short && input.exclude_out_of_stock))
+            .collect();
+        ranked.sort_by_key(|(short, _)| *short);
+        alternative = ranked
+            .iter()
+            .find(|(short, _)| !short)
+            .map(|(_, opt)| render_option(opt, input).description);
+        options = ranked.into_iter().map(|(_, opt)| opt).collect();
+    }
+    options
+        .iter()
+        .take(30)
+        .map(|opt| {
+            let mut output = render_option(opt, input);
+            output.stock_alternative =
+                stock_alternative(&output.stock_shortfalls, alternative.as_deref());
+            output.predicted_inr = dose_response
+                .as_ref()
+                .map(|model| model.predict(opt.weekly_dose_actual));
+            if let (Some(prediction), Some(target)) = (&output.predicted_inr, &target) {
+                output
+                    .warnings
+                    .extend(check_predicted_inr(prediction, target));
+                output
+                    .warnings
+                    .sort_by_key(|w| std::cmp::Reverse(w.severity));
+            }
+            output
+        })
+        .collect()
+}
+
+/// Every option matching `input.weekly_dose`, simplest first.
+fn generate_options(input: &CalculationInput) -> Vec<DosageOption> {
+    generate_options_recording(input, &mut Rejections::default())
+}
+
+/// `generate_options`, noting in `rejections` why candidates were dropped.
+fn generate_options_recording(
+    input: &CalculationInput,
+    rejections: &mut Rejections,
+) -> Vec<DosageOption> {
     let weekly_dose_target = input.weekly_dose;
     if weekly_dose_target < 0.0 {
-        return Ok(serde_wasm_bindgen::to_value(&Vec::<FinalOutput>::new())?);
+        rejections.count(RejectionReason::NegativeDose);
+        return Vec::new();
     }
 
     let mut options: Vec<DosageOption> = Vec::new();
     let mut seen_options: HashSet<String> = HashSet::new();
+    add_week_options(
+        &mut options,
+        &mut seen_options,
+        input,
+        weekly_dose_target,
+        rejections,
+    );
+
+    // --- Case 3: Multi-week cycles, only when no single week fits the target ---
+    if options.is_empty() {
+        add_cycle_options(&mut options, input, weekly_dose_target, rejections);
+    }
+    add_alternating_options(
+        &mut options,
+        &mut seen_options,
+        input,
+        weekly_dose_target,
+        rejections,
+    );
+
+    sort_options(&mut options);
+    options
+}
+
+// --- Option Search ---
+
+/// Collects every single-week (uniform or non-uniform) option matching the weekly target.
+fn add_week_options(
+    options: &mut Vec<DosageOption>,
+    seen_options: &mut HashSet<String>,
+    input: &CalculationInput,
+    weekly_dose_target: f64,
+    rejections: &mut Rejections,
+) {
+    let profile = input.drug.profile();
 
     // --- Case 1: Uniform dose ---
     let daily_dose_target = weekly_dose_target / 7.0;
@@ -118,14 +453,11 @@ pub fn generate_suggestions_rust(input_js: JsValue) -> Result<JsValue, JsValue>
             &input.available_pills,
             input.allow_half,
             min_pills,
-            4,
+            input.max_tablets(),
+            rejections,
         );
         for combo in daily_combos {
-            let actual_weekly_dose = combo
-                .iter()
-                .map(|p| p.mg as f64 * p.count as f64 * if p.half { 0.5 } else { 1.0 })
-                .sum::<f64>()
-                * 7.0;
+            let actual_weekly_dose = combo_dose(&combo) * 7.0;
             if (actual_weekly_dose - weekly_dose_target).abs() < FLOAT_TOLERANCE {
                 let key = format!("uniform-{:?}", &combo);
                 if seen_options.insert(key) {
@@ -149,10 +481,15 @@ pub fn generate_suggestions_rust(input_js: JsValue) -> Result<JsValue, JsValue>
             let (stop_days, special_days) =
                 get_day_indices(num_stop_days, num_special_days, input.special_day_pattern);
 
-            for i in 1..=(ABSOLUTE_MAX_DAILY_DOSE * 2.0) as i32 {
-                let base_dose = i as f64 / 2.0;
-                let normal_day_combos =
-                    find_comb(base_dose, &input.available_pills, input.allow_half, 1, 4);
+            for base_dose in profile.daily_dose_steps() {
+                let normal_day_combos = find_comb(
+                    base_dose,
+                    &input.available_pills,
+                    input.allow_half,
+                    1,
+                    input.max_tablets(),
+                    rejections,
+                );
                 if normal_day_combos.is_empty() {
                     continue;
                 }
@@ -164,9 +501,9 @@ pub fn generate_suggestions_rust(input_js: JsValue) -> Result<JsValue, JsValue>
                         continue;
                     }
                     add_non_uniform_option(
-                        &mut options,
-                        &mut seen_options,
-                        &input,
+                        options,
+                        seen_options,
+                        weekly_dose_target,
                         base_dose,
                         &normal_day_combos,
                         0.0,
@@ -184,9 +521,15 @@ pub fn generate_suggestions_rust(input_js: JsValue) -> Result<JsValue, JsValue>
                     {
                         continue;
                     }
-                    if special_day_dose_target > ABSOLUTE_MAX_DAILY_DOSE
-                        || special_day_dose_target > base_dose * DOSE_MULTIPLIER_LIMIT
-                    {
+                    if special_day_dose_target > profile.max_daily_dose_mg {
+                        rejections.record(RejectionReason::AboveDailyMax, special_day_dose_target);
+                        continue;
+                    }
+                    if special_day_dose_target > base_dose * DOSE_MULTIPLIER_LIMIT {
+                        rejections.record(
+                            RejectionReason::AboveDoseMultiplier,
+                            special_day_dose_target,
+                        );
                         continue;
                     }
 
@@ -195,16 +538,17 @@ pub fn generate_suggestions_rust(input_js: JsValue) -> Result<JsValue, JsValue>
                         &input.available_pills,
                         input.allow_half,
                         1,
-                        4,
+                        input.max_tablets(),
+                        rejections,
                     );
                     if special_day_combos.is_empty() {
                         continue;
                     }
 
                     add_non_uniform_option(
-                        &mut options,
-                        &mut seen_options,
-                        &input,
+                        options,
+                        seen_options,
+                        weekly_dose_target,
                         base_dose,
                         &normal_day_combos,
                         special_day_dose_target,
@@ -216,8 +560,157 @@ pub fn generate_suggestions_rust(input_js: JsValue) -> Result<JsValue, JsValue>
             }
         }
     }
+}
+
+/// Collects 14-day cycles whose average weekly dose matches the target, built from
+/// a heavier week A and a lighter week B that are each expressible as a single week.
+fn add_cycle_options(
+    options: &mut Vec<DosageOption>,
+    input: &CalculationInput,
+    weekly_dose_target: f64,
+    rejections: &mut Rejections,
+) {
+    let cycle_dose_target = weekly_dose_target * CYCLE_WEEKS as f64;
+    let max_week_dose = weekly_dose_target * (1.0 + MAX_CYCLE_WEEK_DEVIATION);
+    let mut seen_options: HashSet<String> = HashSet::new();
+
+    let step = input.drug.profile().dose_step_mg;
+    let mut week_a_dose = (weekly_dose_target / step).floor() * step + step;
+    while week_a_dose <= max_week_dose + FLOAT_TOLERANCE {
+        let week_b_dose = cycle_dose_target - week_a_dose;
+        if week_b_dose < 0.0 || !input.drug.profile().is_dose_step(week_b_dose) {
+            week_a_dose += step;
+            continue;
+        }
 
-    // --- Sort options by complexity ---
+        let week_a_options = best_week_options(input, week_a_dose, rejections);
+        let week_b_options = best_week_options(input, week_b_dose, rejections);
+        for week_a in &week_a_options {
+            for week_b in &week_b_options {
+                let cycle_option = DosageOption::new_cycle(vec![week_a.clone(), week_b.clone()]);
+                if let OptionType::NonUniform(cycle) = &cycle_option.option_type {
+                    if seen_options.insert(format!("cycle-{:?}", cycle)) {
+                        options.push(cycle_option);
+                    }
+                }
+            }
+        }
+        week_a_dose += step;
+    }
+}
+
+/// Collects every-other-day regimens whose effective weekly dose (3.5 days on each
+/// dose) matches the target. The heavier dose is taken on the start date. Skipped for
+/// short half-life drugs.
+fn add_alternating_options(
+    options: &mut Vec<DosageOption>,
+    seen_options: &mut HashSet<String>,
+    input: &CalculationInput,
+    weekly_dose_target: f64,
+    rejections: &mut Rejections,
+) {
+    let profile = input.drug.profile();
+    if !profile.allows_alternating_days() {
+        rejections.count(RejectionReason::AlternatingNotAllowed);
+        return;
+    }
+    let pair_dose_target = weekly_dose_target * 2.0 / 7.0;
+    for first_dose in profile.daily_dose_steps() {
+        let second_dose = pair_dose_target - first_dose;
+        if !profile.is_dose_step(second_dose)
+            || second_dose < FLOAT_TOLERANCE
+            || second_dose >= first_dose
+        {
+            continue;
+        }
+        if first_dose > second_dose * DOSE_MULTIPLIER_LIMIT {
+            rejections.record(RejectionReason::AboveDoseMultiplier, first_dose);
+            continue;
+        }
+
+        let max_tablets = input.max_tablets();
+        let first_combos = find_comb(
+            first_dose,
+            &input.available_pills,
+            input.allow_half,
+            1,
+            max_tablets,
+            rejections,
+        );
+        let second_combos = find_comb(
+            second_dose,
+            &input.available_pills,
+            input.allow_half,
+            1,
+            max_tablets,
+            rejections,
+        );
+        for first in &first_combos {
+            for second in &second_combos {
+                let key = format!("alternating-{:?}-{:?}", first, second);
+                if seen_options.insert(key) {
+                    let opt_type = OptionType::Alternating {
+                        first: first.clone(),
+                        second: second.clone(),
+                        start_day_of_week: input.start_day_of_week as usize,
+                    };
+                    let actual_weekly_dose = (combo_dose(first) + combo_dose(second)) * 3.5;
+                    options.push(DosageOption::new(opt_type, actual_weekly_dose));
+                }
+            }
+        }
+    }
+}
+
+/// The simplest option at the achievable weekly dose closest to `weekly_dose_target`,
+/// trying doses on the drug's step grid up to `window` mg either side that pass `accept`.
+fn nearest_achievable_option(
+    input: &CalculationInput,
+    weekly_dose_target: f64,
+    window: f64,
+    accept: impl Fn(f64) -> bool,
+) -> Option<(DosageOption, f64)> {
+    let step = input.drug.profile().dose_step_mg;
+    let mut candidates: Vec<f64> = (((weekly_dose_target - window) / step).ceil() as i64
+        ..=((weekly_dose_target + window) / step).floor() as i64)
+        .map(|steps| steps as f64 * step)
+        .filter(|&dose| dose > 0.0 && accept(dose))
+        .collect();
+    candidates.sort_by(|a, b| {
+        (a - weekly_dose_target)
+            .abs()
+            .total_cmp(&(b - weekly_dose_target).abs())
+    });
+    let mut candidate_input = input.clone();
+    candidates.into_iter().find_map(|dose| {
+        candidate_input.weekly_dose = dose;
+        generate_options(&candidate_input)
+            .into_iter()
+            .next()
+            .map(|opt| (opt, dose))
+    })
+}
+
+fn best_week_options(
+    input: &CalculationInput,
+    weekly_dose_target: f64,
+    rejections: &mut Rejections,
+) -> Vec<DosageOption> {
+    let mut options = Vec::new();
+    add_week_options(
+        &mut options,
+        &mut HashSet::new(),
+        input,
+        weekly_dose_target,
+        rejections,
+    );
+    sort_options(&mut options);
+    options.truncate(CYCLE_WEEK_CANDIDATES);
+    options
+}
+
+/// Sorts options by complexity, simplest first.
+fn sort_options(options: &mut [DosageOption]) {
     options.sort_by(|a, b| {
         a.half_pill_complexity
             .cmp(&b.half_pill_complexity)
@@ -228,26 +721,19 @@ pub fn generate_suggestions_rust(input_js: JsValue) -> Result<JsValue, JsValue>
             .then_with(|| a.pill_color_count.cmp(&b.pill_color_count))
             .then_with(|| a.total_pill_objects.cmp(&b.total_pill_objects))
     });
-
-    // --- Render final output for Vue ---
-    let final_results: Vec<FinalOutput> = options
-        .iter()
-        .take(30)
-        .map(|opt| render_option(opt, &input))
-        .collect();
-
-    Ok(serde_wasm_bindgen::to_value(&final_results)?)
 }
 
 // --- Combination Finding Logic ---
 
-/// Finds combinations of pills for a target dose using recursion.
+/// Finds combinations of pills for a target dose using recursion, noting in
+/// `rejections` when there are none.
 fn find_comb(
     target: f64,
     available_pills: &[u8],
     allow_half: bool,
     min_pills: u8,
     max_pills: u8,
+    rejections: &mut Rejections,
 ) -> Vec<Vec<Pill>> {
     if target < FLOAT_TOLERANCE {
         return if min_pills == 0 { vec![vec![]] } else { vec![] };
@@ -266,7 +752,8 @@ fn find_comb(
     );
 
     let mut unique_combos = HashSet::new();
-    results
+    let mut duplicate_half = false;
+    let combos: Vec<Vec<Pill>> = results
         .into_iter()
         .filter_map(|combo| {
             if combo.len() < min_pills as usize {
@@ -279,6 +766,7 @@ fn find_comb(
                 }
             }
             if half_counts.values().any(|&count| count > 1) {
+                duplicate_half = true;
                 return None;
             }
 
@@ -296,10 +784,21 @@ fn find_comb(
                 None
             }
         })
-        .collect()
+        .collect();
+
+    if combos.is_empty() {
+        let reason = if duplicate_half {
+            RejectionReason::DuplicateHalf
+        } else {
+            RejectionReason::NotConstructible
+        };
+        rejections.record(reason, target);
+    }
+    combos
 }
 
 // The recursive part of find_comb
+#[allow(clippy::too_many_arguments)]
 fn find_recursive(
     target: f64,
     available_pills: &[u8],
@@ -372,6 +871,13 @@ fn find_recursive(
     );
 }
 
+fn combo_dose(combo: &[Pill]) -> f64 {
+    combo
+        .iter()
+        .map(|p| p.mg as f64 * p.count as f64 * if p.half { 0.5 } else { 1.0 })
+        .sum()
+}
+
 // Aggregates a list like [Pill(5mg), Pill(5mg)] into [Pill(5mg, count: 2)]
 fn aggregate_combo(combo: Vec<Pill>) -> Vec<Pill> {
     let mut aggregated: HashMap<(u8, bool), u8> = HashMap::new();
@@ -388,10 +894,11 @@ fn aggregate_combo(combo: Vec<Pill>) -> Vec<Pill> {
 
 // --- Option Generation and Management ---
 
+#[allow(clippy::too_many_arguments)]
 fn add_non_uniform_option(
     options: &mut Vec<DosageOption>,
     seen_options: &mut HashSet<String>,
-    input: &CalculationInput,
+    weekly_dose_target: f64,
     base_dose: f64,
     normal_day_combos: &[Vec<Pill>],
     special_day_dose_target: f64,
@@ -401,26 +908,20 @@ fn add_non_uniform_option(
 ) {
     for n_combo in normal_day_combos {
         for s_combo in special_day_combos {
-            let mut combo_weekly = vec![Vec::new(); 7];
-            let mut actual_weekly_dose = 0.0;
-
-            for i in 0..7 {
-                let combo_for_day: &[Pill] = if stop_days.contains(&i) {
-                    &[]
-                } else if special_days.contains(&i) {
-                    s_combo.as_slice()
-                } else {
-                    n_combo.as_slice()
-                };
-
-                combo_weekly[i] = combo_for_day.to_vec();
-                actual_weekly_dose += combo_for_day
-                    .iter()
-                    .map(|p| p.mg as f64 * p.count as f64 * if p.half { 0.5 } else { 1.0 })
-                    .sum::<f64>();
-            }
+            let combo_weekly: Vec<Vec<Pill>> = (0..7)
+                .map(|i| {
+                    if stop_days.contains(&i) {
+                        Vec::new()
+                    } else if special_days.contains(&i) {
+                        s_combo.clone()
+                    } else {
+                        n_combo.clone()
+                    }
+                })
+                .collect();
+            let actual_weekly_dose: f64 = combo_weekly.iter().map(|c| combo_dose(c)).sum();
 
-            if (actual_weekly_dose - input.weekly_dose).abs() < FLOAT_TOLERANCE {
+            if (actual_weekly_dose - weekly_dose_target).abs() < FLOAT_TOLERANCE {
                 let key = format!("non-uniform-{:?}", combo_weekly);
                 if seen_options.insert(key) {
                     let opt_type = OptionType::NonUniform(combo_weekly);
@@ -441,37 +942,28 @@ fn add_non_uniform_option(
 // --- Rendering and Formatting ---
 
 fn render_option(option: &DosageOption, input: &CalculationInput) -> FinalOutput {
-    let days_name = ["จ.", "อ.", "พ.", "พฤ.", "ศ.", "ส.", "อา."];
-    let description = option.get_description(&days_name);
+    let description = option.get_description(&DAY_NAMES);
 
     let display_order = [6, 0, 1, 2, 3, 4, 5]; // Sun first for display
+    let cycle_weeks = option.cycle_weeks();
     let mut weekly_schedule: Vec<DaySchedule> = Vec::new();
 
-    for &day_idx in &display_order {
-        let combo = match &option.option_type {
-            OptionType::Uniform(c) => c,
-            OptionType::NonUniform(cw) => &cw[day_idx],
-        };
+    for (week_idx, &day_idx) in
+        (0..cycle_weeks).flat_map(|w| display_order.iter().map(move |d| (w, d)))
+    {
+        let cycle_day = week_idx * 7 + day_idx;
+        let combo = option.combo_for_cycle_day(cycle_day);
 
-        let is_stop_day = option.stop_days.contains(&day_idx);
-        let is_special_day = option.special_days.contains(&day_idx);
+        let is_stop_day = option.stop_days.contains(&cycle_day);
+        let is_special_day = option.special_days.contains(&cycle_day);
 
-        let total_dose: f64 = combo
-            .iter()
-            .map(|p| p.mg as f64 * p.count as f64 * if p.half { 0.5 } else { 1.0 })
-            .sum();
+        let total_dose = combo_dose(combo);
 
-        let pills: Vec<PillRenderData> = combo
-            .iter()
-            .map(|p| PillRenderData {
-                mg: p.mg,
-                count: p.count,
-                is_half: p.half,
-            })
-            .collect();
+        let pills: Vec<PillRenderData> = combo.iter().map(PillRenderData::from).collect();
 
         weekly_schedule.push(DaySchedule {
             day_index: day_idx,
+            week_index: week_idx,
             total_dose,
             pills,
             is_stop_day: is_stop_day || total_dose < FLOAT_TOLERANCE,
@@ -480,37 +972,83 @@ fn render_option(option: &DosageOption, input: &CalculationInput) -> FinalOutput
     }
 
     let total_pills_header = format!("รวมยาถึงวันนัด ({} วัน):", input.days_until_appointment);
-    let pill_lines = calculate_total_pills(
-        option,
-        input.days_until_appointment,
-        input.start_day_of_week,
-    );
+    let (pill_lines, buffer) = option_supply(option, input);
+    let dispensed = dispensed_lines(&pill_lines, &buffer);
+    let stock_shortfalls = stock_shortfalls(&dispensed, &input.stock);
+    let remaining_stock = remaining_stock(&dispensed, &input.stock);
+    let cost = regimen_cost(option, &pill_lines, &input.prices);
+    let change = regimen_from_schedule(&input.current_regimen)
+        .ok()
+        .map(|current| regimen_change(option, &current));
+    let supply_mismatches = input
+        .previous_dispensing
+        .as_ref()
+        .and_then(|previous| {
+            check_home_supply(previous, &input.home_supply, input.start_day_of_week).ok()
+        })
+        .unwrap_or_default();
 
     FinalOutput {
         description,
         weekly_dose_actual: option.weekly_dose_actual,
+        cycle_weeks,
         weekly_schedule,
         total_pills_summary: TotalPillsSummary {
             header: total_pills_header,
             pill_lines,
+            buffer,
+            supply_mismatches,
         },
+        predicted_inr: None,
+        warnings: check_safety(option, input),
+        stock_shortfalls,
+        stock_alternative: None,
+        remaining_stock,
+        cost,
+        change,
     }
 }
 
-fn calculate_total_pills(
+/// Tablets to dispense for `option` from the start day until the appointment,
+/// and for the buffer days after it.
+fn option_supply(
     option: &DosageOption,
-    days_until_appointment: u32,
-    start_day_of_week: u8,
+    input: &CalculationInput,
+) -> (Vec<PillLineSummary>, Option<BufferSummary>) {
+    let mut pill_lines = calculate_total_pills(
+        option.daily_combos(input.start_day_of_week, input.days_until_appointment),
+        &input.home_supply,
+    );
+    apply_pack_rounding(&mut pill_lines, &input.packs, input.rounding);
+    let buffer = buffer_summary(option, input);
+    (pill_lines, buffer)
+}
+
+fn stock_shortfalls_for(option: &DosageOption, input: &CalculationInput) -> Vec<StockShortfall> {
+    let (pill_lines, buffer) = option_supply(option, input);
+    stock_shortfalls(&dispensed_lines(&pill_lines, &buffer), &input.stock)
+}
+
+/// Every line that leaves the pharmacy shelf, buffer included.
+fn dispensed_lines(
+    pill_lines: &[PillLineSummary],
+    buffer: &Option<BufferSummary>,
+) -> Vec<PillLineSummary> {
+    let buffer_lines = buffer.as_ref().map_or(&[][..], |b| &b.pill_lines[..]);
+    [pill_lines, buffer_lines].concat()
+}
+
+/// Counts the tablets needed for a run of consecutive days, one combo per day, and
+/// the net to dispense after the home supply: loose halves cover half doses first,
+/// whole tablets cover the rest.
+fn calculate_total_pills<'a>(
+    daily_combos: impl IntoIterator<Item = &'a [Pill]>,
+    home_supply: &[HomeSupply],
 ) -> Vec<PillLineSummary> {
     let mut half_pill_counts: HashMap<u8, u32> = HashMap::new();
     let mut whole_pill_counts: HashMap<u8, u32> = HashMap::new();
 
-    for day in 0..days_until_appointment {
-        let current_day_index = (start_day_of_week as u32 + day) % 7;
-        let combo_for_day = match &option.option_type {
-            OptionType::Uniform(c) => c,
-            OptionType::NonUniform(cw) => &cw[current_day_index as usize],
-        };
+    for combo_for_day in daily_combos {
         for p in combo_for_day {
             if p.half {
                 *half_pill_counts.entry(p.mg).or_insert(0) += p.count as u32;
@@ -520,26 +1058,52 @@ fn calculate_total_pills(
         }
     }
 
+    let mut strengths: Vec<u8> = whole_pill_counts
+        .keys()
+        .chain(half_pill_counts.keys())
+        .copied()
+        .collect();
+    strengths.sort_unstable_by(|a, b| b.cmp(a));
+    strengths.dedup();
+
     let mut lines: Vec<PillLineSummary> = Vec::new();
-    for &mg in &[5, 3, 2, 1] {
+    for mg in strengths {
         let whole_count = *whole_pill_counts.get(&mg).unwrap_or(&0);
         let half_count = *half_pill_counts.get(&mg).unwrap_or(&0);
         let total_whole_pills_from_halves = half_count / 2;
         let remaining_halves = half_count % 2;
 
-        let dispensed_pills = whole_count + total_whole_pills_from_halves + remaining_halves;
-        if dispensed_pills > 0 {
-            let usage_note = if remaining_halves > 0 {
+        let required_pills = whole_count + total_whole_pills_from_halves + remaining_halves;
+        if required_pills > 0 {
+            let (home_whole, home_halves) = home_supply
+                .iter()
+                .filter(|s| s.mg == mg)
+                .fold((0, 0), |(whole, halves), s| {
+                    (whole + s.whole, halves + s.halves)
+                });
+            let halves_to_cut = half_count.saturating_sub(home_halves);
+            let dispensed_pills =
+                (whole_count + halves_to_cut / 2 + halves_to_cut % 2).saturating_sub(home_whole);
+
+            let mut notes = Vec::new();
+            if remaining_halves > 0 {
                 let actual_used = (whole_count + total_whole_pills_from_halves) as f64
                     + (remaining_halves as f64 * 0.5);
-                format!("(ใช้จริง {:.1} เม็ด)", actual_used)
-            } else {
-                String::new()
-            };
+                notes.push(format!("(ใช้จริง {:.1} เม็ด)", actual_used));
+            }
+            if dispensed_pills < required_pills {
+                notes.push(format!(
+                    "(หักยาเหลือ {} เม็ด)",
+                    required_pills - dispensed_pills
+                ));
+            }
             lines.push(PillLineSummary {
                 mg,
+                required_count: required_pills,
                 dispensed_count: dispensed_pills,
-                usage_note,
+                surplus_count: 0,
+                packs: None,
+                usage_note: notes.join(" "),
             });
         }
     }
@@ -615,22 +1179,143 @@ impl DosageOption {
             half_pill_complexity: 0,
             pill_color_count: 0,
             total_pill_objects: 0,
+            week_options: Vec::new(),
         };
         option.calculate_complexity();
         option
     }
 
+    /// Chains single-week options into one repeating cycle (week A, week B, ...).
+    fn new_cycle(week_options: Vec<DosageOption>) -> Self {
+        let mut cycle = Vec::new();
+        let mut stop_days = Vec::new();
+        let mut special_days = Vec::new();
+        for (week_idx, week) in week_options.iter().enumerate() {
+            cycle.extend((0..7).map(|day| week.combo_for_cycle_day(day).to_vec()));
+            stop_days.extend(week.stop_days.iter().map(|&d| week_idx * 7 + d));
+            special_days.extend(week.special_days.iter().map(|&d| week_idx * 7 + d));
+        }
+        let weekly_dose_actual = week_options
+            .iter()
+            .map(|w| w.weekly_dose_actual)
+            .sum::<f64>()
+            / week_options.len() as f64;
+
+        let mut option = DosageOption::new(OptionType::NonUniform(cycle), weekly_dose_actual);
+        option.num_stop_days = stop_days.len() as u8;
+        option.stop_days = stop_days;
+        option.num_special_days = special_days.len() as u8;
+        option.special_days = special_days;
+        option.week_options = week_options;
+        option.calculate_complexity();
+        option
+    }
+
+    /// An option for a regimen given day by day, e.g. one read back from a written
+    /// schedule. `cycle` holds 7 * N days starting on Monday of week A; a 14-day
+    /// cycle repeating every other day becomes an alternating option.
+    fn from_cycle(mut cycle: Vec<Vec<Pill>>) -> Self {
+        for combo in &mut cycle {
+            combo.sort();
+        }
+        let weekly_dose_actual =
+            cycle.iter().map(|combo| combo_dose(combo)).sum::<f64>() / (cycle.len() / 7) as f64;
+        if cycle.iter().all(|combo| *combo == cycle[0]) {
+            return DosageOption::new(
+                OptionType::Uniform(cycle.swap_remove(0)),
+                weekly_dose_actual,
+            );
+        }
+        if cycle.len() == 14 && (2..14).all(|day| cycle[day] == cycle[day % 2]) {
+            return DosageOption::new(
+                OptionType::Alternating {
+                    first: cycle[0].clone(),
+                    second: cycle[1].clone(),
+                    start_day_of_week: 0,
+                },
+                weekly_dose_actual,
+            );
+        }
+        if cycle.len() > 7 {
+            let week_options = cycle
+                .chunks(7)
+                .map(|week| DosageOption::from_cycle(week.to_vec()))
+                .collect();
+            return DosageOption::new_cycle(week_options);
+        }
+
+        // The most common dose is the base; other doses are special days.
+        let doses: Vec<f64> = cycle.iter().map(|combo| combo_dose(combo)).collect();
+        let base_dose = doses
+            .iter()
+            .copied()
+            .filter(|&dose| dose > FLOAT_TOLERANCE)
+            .max_by_key(|&dose| {
+                doses
+                    .iter()
+                    .filter(|&&d| (d - dose).abs() < FLOAT_TOLERANCE)
+                    .count()
+            })
+            .unwrap_or(0.0);
+        let stop_days: Vec<usize> = (0..7).filter(|&d| doses[d] < FLOAT_TOLERANCE).collect();
+        let special_days: Vec<usize> = (0..7)
+            .filter(|&d| {
+                doses[d] > FLOAT_TOLERANCE && (doses[d] - base_dose).abs() > FLOAT_TOLERANCE
+            })
+            .collect();
+
+        let mut option = DosageOption::new(OptionType::NonUniform(cycle), weekly_dose_actual);
+        option.base_dose = base_dose;
+        option.special_dose = special_days.first().map_or(0.0, |&d| doses[d]);
+        option.num_stop_days = stop_days.len() as u8;
+        option.stop_days = stop_days;
+        option.num_special_days = special_days.len() as u8;
+        option.special_days = special_days;
+        option
+    }
+
+    fn cycle_weeks(&self) -> usize {
+        match &self.option_type {
+            OptionType::Uniform(_) => 1,
+            OptionType::NonUniform(cw) => cw.len() / 7,
+            // Parity repeats on the same weekdays every 14 days.
+            OptionType::Alternating { .. } => 2,
+        }
+    }
+
+    fn combo_for_cycle_day(&self, cycle_day: usize) -> &[Pill] {
+        match &self.option_type {
+            OptionType::Uniform(c) => c,
+            OptionType::NonUniform(cw) => &cw[cycle_day % cw.len()],
+            OptionType::Alternating {
+                first,
+                second,
+                start_day_of_week,
+            } => {
+                if (cycle_day + start_day_of_week).is_multiple_of(2) {
+                    first
+                } else {
+                    second
+                }
+            }
+        }
+    }
+
+    /// The combos taken on each of `days` consecutive days starting on `start_day_of_week`.
+    fn daily_combos(&self, start_day_of_week: u8, days: u32) -> impl Iterator<Item = &[Pill]> {
+        let cycle_days = self.cycle_weeks() * 7;
+        (0..days as usize).map(move |day| {
+            self.combo_for_cycle_day((start_day_of_week as usize + day) % cycle_days)
+        })
+    }
+
     fn calculate_complexity(&mut self) {
         let mut half_pill_strengths = HashSet::new();
         let mut colors = HashSet::new();
         let mut total_pill_objs = 0;
 
-        let combos_to_scan: Vec<Vec<Pill>> = match &self.option_type {
-            OptionType::Uniform(c) => vec![c.clone(); 7],
-            OptionType::NonUniform(cw) => cw.clone(),
-        };
-
-        for day_combo in combos_to_scan {
+        let cycle_weeks = self.cycle_weeks();
+        for day_combo in (0..cycle_weeks * 7).map(|day| self.combo_for_cycle_day(day)) {
             for pill in day_combo {
                 if pill.count > 0 {
                     colors.insert(pill.mg);
@@ -642,29 +1327,46 @@ impl DosageOption {
             }
         }
 
-        self.priority = if let OptionType::Uniform(_) = self.option_type {
-            0
-        } else {
-            1
+        self.priority = match &self.option_type {
+            OptionType::Uniform(_) => 0,
+            OptionType::NonUniform(_) if cycle_weeks == 1 => 1,
+            OptionType::Alternating { .. } => 2,
+            OptionType::NonUniform(_) => 3,
         };
         self.half_pill_complexity = half_pill_strengths.len();
         self.pill_color_count = colors.len();
-        self.total_pill_objects = total_pill_objs;
+        self.total_pill_objects = total_pill_objs / cycle_weeks as u32;
     }
 
     fn get_description(&self, days_name: &[&str]) -> String {
         match &self.option_type {
             OptionType::Uniform(combo) => {
-                let daily_dose: f64 = combo
-                    .iter()
-                    .map(|p| p.mg as f64 * p.count as f64 * if p.half { 0.5 } else { 1.0 })
-                    .sum();
+                let daily_dose = combo_dose(combo);
                 if daily_dose > FLOAT_TOLERANCE {
                     format!("ทุกวัน วันละ {:.1} mg", daily_dose)
                 } else {
                     "หยุดยา".to_string()
                 }
             }
+            OptionType::Alternating { first, second, .. } => format!(
+                "วันเว้นวัน {:.1} mg สลับกับ {:.1} mg (เริ่ม {:.1} mg ในวันแรก)",
+                combo_dose(first),
+                combo_dose(second),
+                combo_dose(first)
+            ),
+            OptionType::NonUniform(_) if !self.week_options.is_empty() => self
+                .week_options
+                .iter()
+                .enumerate()
+                .map(|(week_idx, week)| {
+                    format!(
+                        "สัปดาห์ {}: {}",
+                        (b'A' + week_idx as u8) as char,
+                        week.get_description(days_name)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(" / "),
             OptionType::NonUniform(_) => {
                 let mut parts = Vec::new();
                 if self.base_dose > FLOAT_TOLERANCE {
@@ -699,3 +1401,107 @@ impl DosageOption {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minimal_change_ranking_needs_a_current_regimen() {
+        let mut input = CalculationInput {
+            weekly_dose: 35.0,
+            available_pills: vec![5],
+            rank_by: RankBy::MinimalChange,
+            ..Default::default()
+        };
+        assert!(prepare_calculation_input(&mut input).is_err());
+        input.rank_by = RankBy::Simplicity;
+        assert!(prepare_calculation_input(&mut input).is_ok());
+    }
+
+    #[test]
+    fn odd_weekly_dose_gets_a_two_week_cycle() {
+        // 8.75 mg/week needs 17.5 mg over two weeks; no single week adds up to it.
+        let mut input = CalculationInput {
+            weekly_dose: 8.75,
+            available_pills: vec![2, 3],
+            allow_half: true,
+            days_until_appointment: 28,
+            ..Default::default()
+        };
+        prepare_calculation_input(&mut input).unwrap();
+        let outputs = generate_suggestions(&input);
+        assert!(!outputs.is_empty());
+        for output in &outputs {
+            assert_eq!(output.cycle_weeks, 2);
+            assert!((output.weekly_dose_actual - 8.75).abs() < FLOAT_TOLERANCE);
+            assert_eq!(output.weekly_schedule.len(), 14);
+            let cycle_dose: f64 = output.weekly_schedule.iter().map(|d| d.total_dose).sum();
+            assert!((cycle_dose - 17.5).abs() < FLOAT_TOLERANCE);
+        }
+    }
+
+    #[test]
+    fn short_stock_names_a_split_the_shelf_can_fill() {
+        let mut input = CalculationInput {
+            weekly_dose: 35.0,
+            available_pills: vec![5, 3, 2],
+            days_until_appointment: 28,
+            stock: vec![PillStock {
+                mg: 5,
+                quantity: 10,
+            }],
+            ..Default::default()
+        };
+        prepare_calculation_input(&mut input).unwrap();
+        let outputs = generate_suggestions(&input);
+        assert!(outputs[0].stock_shortfalls.is_empty());
+        assert!(outputs[0].stock_alternative.is_none());
+
+        let short = outputs
+            .iter()
+            .find(|o| !o.stock_shortfalls.is_empty())
+            .unwrap();
+        let alternative = short.stock_alternative.as_ref().unwrap();
+        assert_eq!(alternative.short_strengths, vec![5]);
+        assert_eq!(alternative.description, outputs[0].description);
+        assert!(alternative.message.contains("5 mg"));
+    }
+
+    #[test]
+    fn alternating_parity_follows_the_start_day() {
+        for (start_day_of_week, days_until_appointment) in [(2, 9), (6, 10)] {
+            let input = CalculationInput {
+                weekly_dose: 17.5,
+                available_pills: vec![3, 2],
+                start_day_of_week,
+                days_until_appointment,
+                ..Default::default()
+            };
+            let option = generate_options(&input)
+                .into_iter()
+                .find(|opt| matches!(opt.option_type, OptionType::Alternating { .. }))
+                .unwrap();
+            // The heavier dose falls on the start date, whatever weekday that is.
+            let doses: Vec<f64> = option
+                .daily_combos(start_day_of_week, days_until_appointment)
+                .map(combo_dose)
+                .collect();
+            let expected: Vec<f64> = (0..days_until_appointment)
+                .map(|day| if day % 2 == 0 { 3.0 } else { 2.0 })
+                .collect();
+            assert_eq!(doses, expected);
+
+            let (pill_lines, _) = option_supply(&option, &input);
+            let required = |mg| {
+                pill_lines
+                    .iter()
+                    .find(|line| line.mg == mg)
+                    .map(|line| line.required_count)
+            };
+            let first_days = days_until_appointment.div_ceil(2);
+            assert_eq!(required(3), Some(first_days));
+            assert_eq!(required(2), Some(days_until_appointment - first_days));
+        }
+    }
+}
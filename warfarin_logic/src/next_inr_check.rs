@@ -0,0 +1,269 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::date::CalendarDate;
+use crate::indication::InrTargetRequest;
+use crate::interactions::{CoMedicationChange, DrugInteraction, InteractionTable};
+use crate::ttr::InrResult;
+
+// Test intervals in days, shortest first.
+const UNSTABLE_INTERVAL: (u32, u32) = (3, 7);
+const DOSE_CHANGE_INTERVAL: (u32, u32) = (7, 14);
+const IN_RANGE_INTERVALS: [(u32, u32); 3] = [(14, 21), (21, 28), (28, 84)];
+
+#[derive(Deserialize, Debug)]
+pub struct NextInrCheckInput {
+    pub visit_date: CalendarDate,
+    pub results: Vec<InrResult>, // recent INRs, any order
+    #[serde(flatten)]
+    pub target: InrTargetRequest,
+    #[serde(default)]
+    pub dose_changed: bool,
+    #[serde(default)]
+    pub medication_changes: Vec<CoMedicationChange>,
+    pub interaction_table: Option<Vec<DrugInteraction>>, // replaces the embedded table
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum CheckReason {
+    NoResults,
+    InteractingDrug,
+    OutOfRange,
+    DoseChanged,
+    InRange,
+}
+
+#[derive(Serialize, Debug)]
+pub struct NextInrCheck {
+    pub reason: CheckReason,
+    pub description: String,
+    pub consecutive_in_range: usize,
+    pub earliest_date: CalendarDate,
+    pub latest_date: CalendarDate,
+    // Ready for `CalculationInput::days_until_appointment`.
+    pub min_days_until_appointment: u32,
+    pub max_days_until_appointment: u32,
+}
+
+#[wasm_bindgen]
+pub fn recommend_next_inr_check_rust(input_js: JsValue) -> Result<JsValue, JsValue> {
+    let input: NextInrCheckInput =
+        serde_wasm_bindgen::from_value(input_js).map_err(|e| e.to_string())?;
+    let table = match &input.interaction_table {
+        Some(entries) => InteractionTable::try_from(entries.clone())?,
+        None => InteractionTable::embedded(),
+    };
+    let check = recommend_next_inr_check(&input, &table)?;
+    Ok(serde_wasm_bindgen::to_value(&check)?)
+}
+
+/// Recommends when to test the INR next. The most cautious rule wins: a newly
+/// started or stopped interacting drug, then an out-of-range last result, then a
+/// dose change; otherwise the interval lengthens with each consecutive in-range result.
+pub fn recommend_next_inr_check(
+    input: &NextInrCheckInput,
+    table: &InteractionTable,
+) -> Result<NextInrCheck, String> {
+    let default_target = input.target.resolve()?;
+    if let Some(r) = input.results.iter().find(|r| !r.inr.is_finite()) {
+        return Err(format!("ค่า INR วันที่ {} ไม่ถูกต้อง", r.date));
+    }
+    let mut results: Vec<&InrResult> = input.results.iter().collect();
+    results.sort_by_key(|r| std::cmp::Reverse(r.date));
+
+    let mut consecutive_in_range = 0;
+    for result in &results {
+        let target = if result.target.is_empty() {
+            default_target
+        } else {
+            result.target.resolve()?
+        };
+        if result.inr < target.low || result.inr > target.high {
+            break;
+        }
+        consecutive_in_range += 1;
+    }
+
+    let interacting_drugs: Vec<&str> = input
+        .medication_changes
+        .iter()
+        .filter_map(|change| table.find(&change.drug))
+        .map(|entry| entry.drug.as_str())
+        .collect();
+
+    let (reason, (min_days, max_days), description) = if results.is_empty() {
+        (
+            CheckReason::NoResults,
+            UNSTABLE_INTERVAL,
+            "ยังไม่มีผล INR ควรตรวจภายใน 1 สัปดาห์".to_string(),
+        )
+    } else if !interacting_drugs.is_empty() {
+        (
+            CheckReason::InteractingDrug,
+            UNSTABLE_INTERVAL,
+            format!(
+                "มีการเปลี่ยนแปลงยาที่มีปฏิกิริยากับยาต้านการแข็งตัวของเลือด ({}) ควรตรวจ INR ภายใน 1 สัปดาห์",
+                interacting_drugs.join(", ")
+            ),
+        )
+    } else if consecutive_in_range == 0 {
+        (
+            CheckReason::OutOfRange,
+            DOSE_CHANGE_INTERVAL,
+            "INR ล่าสุดอยู่นอกช่วงเป้าหมาย ควรตรวจซ้ำใน 1–2 สัปดาห์".to_string(),
+        )
+    } else if input.dose_changed {
+        (
+            CheckReason::DoseChanged,
+            DOSE_CHANGE_INTERVAL,
+            "เพิ่งปรับขนาดยา ควรตรวจ INR ใน 1–2 สัปดาห์".to_string(),
+        )
+    } else {
+        let interval = IN_RANGE_INTERVALS[consecutive_in_range.min(IN_RANGE_INTERVALS.len()) - 1];
+        (
+            CheckReason::InRange,
+            interval,
+            format!(
+                "INR อยู่ในช่วงเป้าหมายติดต่อกัน {} ครั้ง นัดตรวจได้ใน {}–{} สัปดาห์",
+                consecutive_in_range,
+                interval.0 / 7,
+                interval.1 / 7
+            ),
+        )
+    };
+
+    Ok(NextInrCheck {
+        reason,
+        description,
+        consecutive_in_range,
+        earliest_date: input.visit_date.add_days(min_days as i64),
+        latest_date: input.visit_date.add_days(max_days as i64),
+        min_days_until_appointment: min_days,
+        max_days_until_appointment: max_days,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interactions::MedicationAction;
+
+    fn visit() -> CalendarDate {
+        CalendarDate::from_ymd(2026, 10, 14).unwrap()
+    }
+
+    // INRs from oldest to newest, four weeks apart, the last one on the visit.
+    fn input(inrs: &[f64]) -> NextInrCheckInput {
+        NextInrCheckInput {
+            visit_date: visit(),
+            results: inrs
+                .iter()
+                .rev()
+                .enumerate()
+                .map(|(i, &inr)| InrResult {
+                    date: visit().add_days(-28 * i as i64),
+                    inr,
+                    target: InrTargetRequest::default(),
+                })
+                .collect(),
+            target: InrTargetRequest::default(),
+            dose_changed: false,
+            medication_changes: Vec::new(),
+            interaction_table: None,
+        }
+    }
+
+    fn check(input: &NextInrCheckInput) -> NextInrCheck {
+        recommend_next_inr_check(input, &InteractionTable::embedded()).unwrap()
+    }
+
+    fn days(check: &NextInrCheck) -> (u32, u32) {
+        (
+            check.min_days_until_appointment,
+            check.max_days_until_appointment,
+        )
+    }
+
+    #[test]
+    fn no_results_checks_within_a_week() {
+        let check = check(&input(&[]));
+        assert_eq!(check.reason, CheckReason::NoResults);
+        assert_eq!(days(&check), UNSTABLE_INTERVAL);
+        assert_eq!(check.earliest_date, visit().add_days(3));
+    }
+
+    #[test]
+    fn interacting_drug_wins_over_an_in_range_inr() {
+        let mut input = input(&[2.5, 2.5, 2.5]);
+        input.medication_changes = vec![
+            CoMedicationChange {
+                drug: "paracetamol-x".to_string(),
+                action: MedicationAction::Start,
+            },
+            CoMedicationChange {
+                drug: " Cordarone ".to_string(),
+                action: MedicationAction::Start,
+            },
+        ];
+        let check = check(&input);
+        assert_eq!(check.reason, CheckReason::InteractingDrug);
+        assert_eq!(days(&check), UNSTABLE_INTERVAL);
+        assert!(check.description.contains("amiodarone"));
+        assert!(!check.description.contains("paracetamol-x"));
+    }
+
+    #[test]
+    fn out_of_range_wins_over_a_dose_change() {
+        let mut input = input(&[2.5, 2.5, 3.4]);
+        input.dose_changed = true;
+        let check = check(&input);
+        assert_eq!(check.reason, CheckReason::OutOfRange);
+        assert_eq!(check.consecutive_in_range, 0);
+        assert_eq!(days(&check), DOSE_CHANGE_INTERVAL);
+    }
+
+    #[test]
+    fn dose_change_checks_within_two_weeks() {
+        let mut input = input(&[2.5, 2.5]);
+        input.dose_changed = true;
+        let check = check(&input);
+        assert_eq!(check.reason, CheckReason::DoseChanged);
+        assert_eq!(days(&check), DOSE_CHANGE_INTERVAL);
+    }
+
+    #[test]
+    fn interval_lengthens_with_consecutive_in_range_results() {
+        for (inrs, count, interval) in [
+            (&[3.5, 2.5][..], 1, IN_RANGE_INTERVALS[0]),
+            (&[3.5, 2.5, 2.5][..], 2, IN_RANGE_INTERVALS[1]),
+            (&[2.5, 2.5, 2.5][..], 3, IN_RANGE_INTERVALS[2]),
+            (&[2.5, 2.5, 2.5, 2.5, 2.5][..], 5, IN_RANGE_INTERVALS[2]),
+        ] {
+            let check = check(&input(inrs));
+            assert_eq!(check.reason, CheckReason::InRange);
+            assert_eq!(check.consecutive_in_range, count);
+            assert_eq!(days(&check), interval);
+            assert_eq!(check.latest_date, visit().add_days(interval.1 as i64));
+        }
+    }
+
+    #[test]
+    fn counts_against_each_result_s_own_target() {
+        let mut input = input(&[3.2, 3.2]);
+        input.results[1].target = InrTargetRequest {
+            indication: Some(crate::indication::Indication::MechanicalMitralValve),
+            ..Default::default()
+        };
+        // The older 3.2 was in range for 2.5–3.5; the latest is above 2.0–3.0.
+        assert_eq!(check(&input).reason, CheckReason::OutOfRange);
+        input.target = input.results[1].target;
+        assert_eq!(check(&input).consecutive_in_range, 2);
+    }
+
+    #[test]
+    fn rejects_non_finite_inr() {
+        let input = input(&[2.5, f64::NAN]);
+        assert!(recommend_next_inr_check(&input, &InteractionTable::embedded()).is_err());
+    }
+}
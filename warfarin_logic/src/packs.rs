@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+
+use crate::PillLineSummary;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum RoundingPolicy {
+    #[default]
+    Exact,
+    Strip,
+    Box,
+}
+
+/// How one strength is packed.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PackSize {
+    pub mg: u8,
+    pub tablets_per_strip: u32,
+    pub strips_per_box: u32,
+}
+
+/// A dispensed quantity broken down into whole boxes, the strips left over and
+/// loose tablets cut from a strip.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct PackCount {
+    pub boxes: u32,
+    pub strips: u32,
+    pub loose_tablets: u32,
+}
+
+pub(crate) fn check_packs(packs: &[PackSize]) -> Result<(), String> {
+    match packs
+        .iter()
+        .find(|p| p.tablets_per_strip.checked_mul(p.strips_per_box).is_none())
+    {
+        Some(p) => Err(format!("ขนาดบรรจุยา {} mg ไม่ถูกต้อง", p.mg)),
+        None => Ok(()),
+    }
+}
+
+/// Rounds each line up to whole strips or boxes where its strength has a pack
+/// size, and reports the packs and the surplus tablets the patient keeps.
+pub(crate) fn apply_pack_rounding(
+    pill_lines: &mut [PillLineSummary],
+    packs: &[PackSize],
+    policy: RoundingPolicy,
+) {
+    for line in pill_lines.iter_mut() {
+        let Some(pack) = packs
+            .iter()
+            .find(|p| p.mg == line.mg && p.tablets_per_strip > 0 && p.strips_per_box > 0)
+        else {
+            continue;
+        };
+        let Some(box_size) = pack.tablets_per_strip.checked_mul(pack.strips_per_box) else {
+            continue;
+        };
+        let net = line.dispensed_count;
+        let rounded = match policy {
+            RoundingPolicy::Exact => Some(net),
+            RoundingPolicy::Strip => net
+                .div_ceil(pack.tablets_per_strip)
+                .checked_mul(pack.tablets_per_strip),
+            RoundingPolicy::Box => net.div_ceil(box_size).checked_mul(box_size),
+        };
+        // A pack too large to round to is dispensed as counted.
+        let Some(rounded) = rounded else {
+            continue;
+        };
+        line.dispensed_count = rounded;
+        line.surplus_count = rounded - net;
+        line.packs = Some(PackCount {
+            boxes: rounded / box_size,
+            strips: rounded % box_size / pack.tablets_per_strip,
+            loose_tablets: rounded % pack.tablets_per_strip,
+        });
+        if line.surplus_count > 0 {
+            let note = format!("(ปัดขึ้นเกิน {} เม็ด)", line.surplus_count);
+            line.usage_note = if line.usage_note.is_empty() {
+                note
+            } else {
+                format!("{} {}", line.usage_note, note)
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(mg: u8, dispensed_count: u32) -> PillLineSummary {
+        PillLineSummary {
+            mg,
+            required_count: dispensed_count,
+            dispensed_count,
+            surplus_count: 0,
+            packs: None,
+            usage_note: String::new(),
+        }
+    }
+
+    fn packs() -> Vec<PackSize> {
+        vec![PackSize {
+            mg: 3,
+            tablets_per_strip: 10,
+            strips_per_box: 5,
+        }]
+    }
+
+    #[test]
+    fn exact_keeps_the_count_and_breaks_it_into_packs() {
+        let mut lines = [line(3, 63)];
+        apply_pack_rounding(&mut lines, &packs(), RoundingPolicy::Exact);
+        assert_eq!((lines[0].dispensed_count, lines[0].surplus_count), (63, 0));
+        let packs = lines[0].packs.as_ref().unwrap();
+        assert_eq!((packs.boxes, packs.strips, packs.loose_tablets), (1, 1, 3));
+        assert!(lines[0].usage_note.is_empty());
+    }
+
+    #[test]
+    fn strip_rounds_up_to_whole_strips() {
+        let mut lines = [line(3, 23)];
+        apply_pack_rounding(&mut lines, &packs(), RoundingPolicy::Strip);
+        assert_eq!((lines[0].dispensed_count, lines[0].surplus_count), (30, 7));
+        let packs = lines[0].packs.as_ref().unwrap();
+        assert_eq!((packs.boxes, packs.strips, packs.loose_tablets), (0, 3, 0));
+        assert_eq!(lines[0].usage_note, "(ปัดขึ้นเกิน 7 เม็ด)");
+    }
+
+    #[test]
+    fn box_rounds_up_to_whole_boxes() {
+        let mut lines = [line(3, 51)];
+        apply_pack_rounding(&mut lines, &packs(), RoundingPolicy::Box);
+        assert_eq!(
+            (lines[0].dispensed_count, lines[0].surplus_count),
+            (100, 49)
+        );
+        let packs = lines[0].packs.as_ref().unwrap();
+        assert_eq!((packs.boxes, packs.strips, packs.loose_tablets), (2, 0, 0));
+    }
+
+    #[test]
+    fn oversized_packs_are_rejected_or_left_unrounded() {
+        let huge = vec![PackSize {
+            mg: 3,
+            tablets_per_strip: 100_000,
+            strips_per_box: 100_000,
+        }];
+        assert!(check_packs(&huge).is_err());
+        assert!(check_packs(&packs()).is_ok());
+
+        let mut lines = [line(3, 23)];
+        apply_pack_rounding(&mut lines, &huge, RoundingPolicy::Box);
+        assert_eq!(lines[0].dispensed_count, 23);
+
+        let strip = vec![PackSize {
+            mg: 3,
+            tablets_per_strip: u32::MAX - 1,
+            strips_per_box: 1,
+        }];
+        let mut lines = [line(3, u32::MAX)];
+        apply_pack_rounding(&mut lines, &strip, RoundingPolicy::Strip);
+        assert_eq!(lines[0].dispensed_count, u32::MAX);
+    }
+
+    #[test]
+    fn lines_without_a_pack_size_are_left_alone() {
+        let mut lines = [line(5, 23), line(3, 0)];
+        apply_pack_rounding(&mut lines, &packs(), RoundingPolicy::Strip);
+        assert_eq!(lines[0].dispensed_count, 23);
+        assert!(lines[0].packs.is_none());
+        // Nothing to dispense stays at zero.
+        assert_eq!(lines[1].dispensed_count, 0);
+    }
+}
@@ -0,0 +1,323 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::date::CalendarDate;
+use crate::drug::VkaDrug;
+use crate::plan::{
+    boosted_combo, day_label, plan_calendar, plan_table, regimen_from_schedule,
+    summarize_plan_pills, PlanPhase, PlannedDay,
+};
+use crate::table::TableFormat;
+use crate::{DaySchedule, TotalPillsSummary};
+
+// Day offsets relative to the procedure date (day 0).
+const LMWH_START_DAY: i64 = -3;
+const LAST_PREOP_LMWH_DAY: i64 = -1;
+const PREOP_INR_CHECK_DAY: i64 = -1;
+const POSTOP_INR_CHECK_DAY: i64 = 5;
+
+const ENOXAPARIN_MG_PER_KG: f64 = 1.0;
+const ENOXAPARIN_ROUNDING_MG: f64 = 10.0;
+const SEVERE_RENAL_IMPAIRMENT_CRCL: f64 = 30.0;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BleedingRisk {
+    Low,
+    High,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThromboembolicRisk {
+    Low,
+    Moderate,
+    High,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PerioperativeInput {
+    #[serde(default)]
+    pub drug: VkaDrug,
+    pub visit_date: CalendarDate,
+    pub procedure_date: CalendarDate,
+    pub bleeding_risk: BleedingRisk,
+    pub thromboembolic_risk: ThromboembolicRisk,
+    pub current_regimen: Vec<DaySchedule>,
+    pub weight_kg: f64,
+    pub crcl_ml_min: Option<f64>,
+    #[serde(default)]
+    pub boost_first_dose: bool,
+    #[serde(default)]
+    pub format: Option<TableFormat>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DoseTime {
+    Morning,
+    Evening,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LmwhDose {
+    pub date: CalendarDate,
+    pub time: DoseTime,
+    pub dose_mg: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PerioperativePlan {
+    pub description: String,
+    pub last_vka_dose: CalendarDate,
+    pub bridging: bool,
+    pub lmwh_doses: Vec<LmwhDose>,
+    pub preop_inr_check: CalendarDate,
+    pub vka_restart: CalendarDate,
+    pub postop_inr_check: CalendarDate,
+    pub days: Vec<PlannedDay>,
+    pub total_pills_summary: TotalPillsSummary,
+    pub table: Option<String>, // rendered in the requested `format`
+    pub calendar: String,      // iCalendar (.ics), one all-day event per day
+}
+
+#[wasm_bindgen]
+pub fn plan_perioperative_rust(input_js: JsValue) -> Result<JsValue, JsValue> {
+    let input: PerioperativeInput =
+        serde_wasm_bindgen::from_value(input_js).map_err(|e| e.to_string())?;
+    let plan = plan_perioperative(&input)?;
+    Ok(serde_wasm_bindgen::to_value(&plan)?)
+}
+
+/// Plans VKA interruption, bridging and restart from the visit to the post-op INR check.
+pub fn plan_perioperative(input: &PerioperativeInput) -> Result<PerioperativePlan, String> {
+    let cycle = regimen_from_schedule(&input.current_regimen)?;
+    let profile = input.drug.profile();
+    let procedure = input.procedure_date;
+    let last_vka_dose = procedure.add_days(-(profile.preop_stop_days as i64) - 1);
+    if input.visit_date > last_vka_dose {
+        return Err(format!(
+            "ต้องนัดก่อนวันผ่าตัดอย่างน้อย {} วันเพื่อหยุด{} {} วัน",
+            profile.preop_stop_days + 1,
+            profile.name,
+            profile.preop_stop_days
+        ));
+    }
+    if input.weight_kg <= 0.0 {
+        return Err("กรุณาระบุน้ำหนักตัว".to_string());
+    }
+
+    let preop_inr_check = procedure.add_days(PREOP_INR_CHECK_DAY);
+    let postop_inr_check = procedure.add_days(POSTOP_INR_CHECK_DAY);
+    // Restart the evening of surgery unless bleeding risk asks for another day.
+    let vka_restart = match input.bleeding_risk {
+        BleedingRisk::Low => procedure,
+        BleedingRisk::High => procedure.add_days(1),
+    };
+
+    let bridging = input.thromboembolic_risk == ThromboembolicRisk::High;
+    let lmwh_doses = if bridging {
+        schedule_lmwh(input, postop_inr_check)
+    } else {
+        Vec::new()
+    };
+
+    let visit_day_of_week = input.visit_date.day_of_week();
+    let mut days = Vec::new();
+    for offset in 0..input.visit_date.days_until(postop_inr_check) {
+        let date = input.visit_date.add_days(offset);
+        // The current regimen is assumed to be in week A during the visit week.
+        let combo = &cycle[(visit_day_of_week + offset as usize) % cycle.len()];
+        let mut day = if date <= last_vka_dose {
+            PlannedDay::new(date, PlanPhase::CurrentRegimen, combo)
+        } else if date < vka_restart {
+            PlannedDay::new(date, PlanPhase::Interrupted, &[])
+        } else if date == vka_restart && input.boost_first_dose {
+            match boosted_combo(combo, profile) {
+                Some(boosted) => PlannedDay::new(date, PlanPhase::Boost, &boosted),
+                None => PlannedDay::new(date, PlanPhase::CurrentRegimen, combo),
+            }
+        } else {
+            PlannedDay::new(date, PlanPhase::CurrentRegimen, combo)
+        };
+
+        if date == last_vka_dose {
+            day.notes.push(format!("{}มื้อสุดท้ายก่อนผ่าตัด", profile.name));
+        }
+        if date == preop_inr_check {
+            day.notes
+                .push("ตรวจ INR ก่อนผ่าตัด (หาก INR > 1.5 พิจารณาวิตามินเค 1–2 mg)".to_string());
+        }
+        if date == procedure {
+            day.notes.push("วันผ่าตัด/หัตถการ".to_string());
+        }
+        if date == vka_restart {
+            day.notes.push(format!("เริ่ม{}ใหม่ (มื้อเย็น)", profile.name));
+        }
+        for dose in lmwh_doses.iter().filter(|d| d.date == date) {
+            let time = match dose.time {
+                DoseTime::Morning => "เช้า",
+                DoseTime::Evening => "เย็น",
+            };
+            day.notes
+                .push(format!("enoxaparin {:.0} mg SC ({})", dose.dose_mg, time));
+        }
+        days.push(day);
+    }
+
+    let mut parts = vec![
+        format!("หยุด{}หลัง {}", profile.name, day_label(last_vka_dose)),
+        format!("ตรวจ INR {}", day_label(preop_inr_check)),
+    ];
+    match input.thromboembolic_risk {
+        ThromboembolicRisk::High => parts.push(format!(
+            "bridging ด้วย enoxaparin เริ่ม {}",
+            day_label(procedure.add_days(LMWH_START_DAY))
+        )),
+        ThromboembolicRisk::Moderate => parts.push("พิจารณา bridging เป็นรายกรณี".to_string()),
+        ThromboembolicRisk::Low => parts.push("ไม่ต้อง bridging".to_string()),
+    }
+    parts.push(format!("เริ่ม{}ใหม่ {}", profile.name, day_label(vka_restart)));
+    parts.push(format!("ตรวจ INR หลังผ่าตัด {}", day_label(postop_inr_check)));
+
+    let total_pills_summary = summarize_plan_pills(&days);
+    let table = input
+        .format
+        .map(|format| plan_table(&days, profile).render(format));
+    let calendar = plan_calendar(&days, profile);
+    Ok(PerioperativePlan {
+        description: parts.join(", "),
+        last_vka_dose,
+        bridging,
+        lmwh_doses,
+        preop_inr_check,
+        vka_restart,
+        postop_inr_check,
+        days,
+        total_pills_summary,
+        table,
+        calendar,
+    })
+}
+
+/// Enoxaparin 1 mg/kg every 12 hours (every 24 hours when CrCl < 30 mL/min). The
+/// last pre-op dose, 24 hours before surgery, is half the daily dose; post-op doses
+/// resume after 24 hours (48–72 hours at high bleeding risk) until the INR check.
+fn schedule_lmwh(input: &PerioperativeInput, until: CalendarDate) -> Vec<LmwhDose> {
+    let procedure = input.procedure_date;
+    let once_daily = input
+        .crcl_ml_min
+        .is_some_and(|crcl| crcl < SEVERE_RENAL_IMPAIRMENT_CRCL);
+    let full_dose = round_enoxaparin(input.weight_kg * ENOXAPARIN_MG_PER_KG);
+    // Rounded down so renal patients never get more than half.
+    let half_daily_dose = if once_daily {
+        (full_dose / 2.0 / ENOXAPARIN_ROUNDING_MG).floor() * ENOXAPARIN_ROUNDING_MG
+    } else {
+        full_dose
+    };
+    let postop_start = match input.bleeding_risk {
+        BleedingRisk::Low => procedure.add_days(1),
+        BleedingRisk::High => procedure.add_days(3),
+    };
+
+    let mut doses = Vec::new();
+    let mut push_day = |date: CalendarDate| {
+        doses.push(LmwhDose {
+            date,
+            time: DoseTime::Morning,
+            dose_mg: full_dose,
+        });
+        if !once_daily {
+            doses.push(LmwhDose {
+                date,
+                time: DoseTime::Evening,
+                dose_mg: full_dose,
+            });
+        }
+    };
+    let mut date = procedure.add_days(LMWH_START_DAY);
+    while date < procedure.add_days(LAST_PREOP_LMWH_DAY) {
+        push_day(date);
+        date = date.add_days(1);
+    }
+    date = postop_start;
+    while date < until {
+        push_day(date);
+        date = date.add_days(1);
+    }
+    doses.push(LmwhDose {
+        date: procedure.add_days(LAST_PREOP_LMWH_DAY),
+        time: DoseTime::Morning,
+        dose_mg: half_daily_dose,
+    });
+    doses.sort_by_key(|d| (d.date, d.time == DoseTime::Evening));
+    doses
+}
+
+fn round_enoxaparin(dose_mg: f64) -> f64 {
+    (dose_mg / ENOXAPARIN_ROUNDING_MG).round() * ENOXAPARIN_ROUNDING_MG
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PillRenderData;
+
+    fn input(weight_kg: f64, crcl_ml_min: Option<f64>) -> PerioperativeInput {
+        PerioperativeInput {
+            drug: VkaDrug::default(),
+            visit_date: CalendarDate::from_ymd(2026, 10, 1).unwrap(),
+            procedure_date: CalendarDate::from_ymd(2026, 10, 14).unwrap(),
+            bleeding_risk: BleedingRisk::Low,
+            thromboembolic_risk: ThromboembolicRisk::High,
+            current_regimen: (0..7)
+                .map(|day_index| DaySchedule {
+                    day_index,
+                    week_index: 0,
+                    total_dose: 3.0,
+                    pills: vec![PillRenderData {
+                        mg: 3,
+                        count: 1,
+                        is_half: false,
+                    }],
+                    is_stop_day: false,
+                    is_special_day: false,
+                })
+                .collect(),
+            weight_kg,
+            crcl_ml_min,
+            boost_first_dose: false,
+            format: Some(TableFormat::Csv),
+        }
+    }
+
+    fn last_preop_lmwh_dose(plan: &PerioperativePlan) -> f64 {
+        let day = CalendarDate::from_ymd(2026, 10, 13).unwrap();
+        plan.lmwh_doses
+            .iter()
+            .find(|d| d.date == day)
+            .unwrap()
+            .dose_mg
+    }
+
+    #[test]
+    fn renal_half_dose_rounds_down() {
+        let plan = plan_perioperative(&input(70.0, Some(20.0))).unwrap();
+        assert_eq!(last_preop_lmwh_dose(&plan), 30.0);
+        let plan = plan_perioperative(&input(70.0, None)).unwrap();
+        assert_eq!(last_preop_lmwh_dose(&plan), 70.0);
+    }
+
+    #[test]
+    fn exports_one_row_and_one_event_per_day() {
+        let plan = plan_perioperative(&input(70.0, None)).unwrap();
+        let table = plan.table.unwrap();
+        assert_eq!(table.lines().count(), plan.days.len() + 1);
+        assert_eq!(
+            plan.calendar.matches("BEGIN:VEVENT").count(),
+            plan.days.len()
+        );
+        assert!(plan.calendar.contains("DTSTART;VALUE=DATE:20261014\r\n"));
+        assert!(plan.calendar.split("\r\n").all(|line| line.len() <= 75));
+    }
+}
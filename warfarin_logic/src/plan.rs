@@ -0,0 +1,261 @@
+use serde::Serialize;
+
+use crate::date::CalendarDate;
+use crate::drug::VkaProfile;
+use crate::table::Table;
+use crate::{
+    calculate_total_pills, combo_dose, DaySchedule, Pill, PillRenderData, TotalPillsSummary,
+    CYCLE_WEEKS, DAY_NAMES, FLOAT_TOLERANCE,
+};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PlanPhase {
+    CurrentRegimen,
+    NewRegimen,
+    Hold,
+    Boost,
+    Interrupted,
+    Overlap, // the new drug's tablets, taken alongside the old drug
+}
+
+/// One calendar day of a dated plan, in the same shape as a `DaySchedule` entry.
+#[derive(Serialize, Clone, Debug)]
+pub struct PlannedDay {
+    pub date: CalendarDate,
+    pub day_index: usize,
+    pub phase: PlanPhase,
+    pub total_dose: f64,
+    pub pills: Vec<PillRenderData>,
+    pub is_stop_day: bool,
+    pub notes: Vec<String>, // other events on this day, e.g. injections or INR checks
+}
+
+impl PlannedDay {
+    pub(crate) fn new(date: CalendarDate, phase: PlanPhase, combo: &[Pill]) -> Self {
+        let total_dose = combo_dose(combo);
+        PlannedDay {
+            date,
+            day_index: date.day_of_week(),
+            phase,
+            total_dose,
+            pills: combo.iter().map(PillRenderData::from).collect(),
+            is_stop_day: total_dose < FLOAT_TOLERANCE,
+            notes: Vec::new(),
+        }
+    }
+
+    pub(crate) fn combo(&self) -> Vec<Pill> {
+        self.pills.iter().map(Pill::from).collect()
+    }
+}
+
+/// One extra daily dose on top of `combo`, or `None` if that would exceed the daily ceiling.
+pub(crate) fn boosted_combo(combo: &[Pill], profile: &VkaProfile) -> Option<Vec<Pill>> {
+    if combo_dose(combo) * 2.0 > profile.max_daily_dose_mg {
+        return None;
+    }
+    Some(
+        combo
+            .iter()
+            .map(|p| Pill {
+                count: p.count * 2,
+                ..p.clone()
+            })
+            .collect(),
+    )
+}
+
+/// A repeating regimen read back from the `weekly_schedule` the generator emits.
+/// Entry `week_index * 7 + day_index` holds that day's tablets; week A starts on
+/// the Monday of the week the regimen is applied from.
+pub(crate) fn regimen_from_schedule(schedule: &[DaySchedule]) -> Result<Vec<Vec<Pill>>, String> {
+    if let Some(day) = schedule.iter().find(|d| d.week_index >= CYCLE_WEEKS) {
+        return Err(format!("week_index ไม่ถูกต้อง: {}", day.week_index));
+    }
+    let weeks = schedule.iter().map(|d| d.week_index + 1).max().unwrap_or(0);
+    let mut cycle: Vec<Option<Vec<Pill>>> = vec![None; weeks * 7];
+    for day in schedule {
+        if day.day_index >= 7 {
+            return Err(format!("day_index ไม่ถูกต้อง: {}", day.day_index));
+        }
+        cycle[day.week_index * 7 + day.day_index] =
+            Some(day.pills.iter().map(Pill::from).collect());
+    }
+    if cycle.is_empty() || cycle.iter().any(Option::is_none) {
+        return Err("ตารางยาต้องระบุครบทุกวันของสัปดาห์".to_string());
+    }
+    Ok(cycle.into_iter().flatten().collect())
+}
+
+pub(crate) fn summarize_plan_pills(days: &[PlannedDay]) -> TotalPillsSummary {
+    let combos: Vec<Vec<Pill>> = days.iter().map(PlannedDay::combo).collect();
+    TotalPillsSummary {
+        header: format!("รวมยาถึงวันนัด ({} วัน):", days.len()),
+        pill_lines: calculate_total_pills(combos.iter().map(Vec::as_slice), &[]),
+        buffer: None,
+        supply_mismatches: Vec::new(),
+    }
+}
+
+/// Short Thai label such as "พ. 21/10".
+pub(crate) fn day_label(date: CalendarDate) -> String {
+    let (_, month, day) = date.ymd();
+    format!("{} {}/{}", DAY_NAMES[date.day_of_week()], day, month)
+}
+
+/// "งดยา" or the day's dose, e.g. "วาร์ฟาริน 4.5 mg".
+fn dose_label(day: &PlannedDay, profile: &VkaProfile) -> String {
+    if day.is_stop_day {
+        "งดยา".to_string()
+    } else {
+        format!("{} {:.1} mg", profile.name, day.total_dose)
+    }
+}
+
+/// The tablets to take, e.g. "5 mg 1 เม็ด + 3 mg ครึ่งเม็ด".
+fn tablets_label(pills: &[PillRenderData]) -> String {
+    pills
+        .iter()
+        .map(|p| match (p.is_half, p.count) {
+            (true, 1) => format!("{} mg ครึ่งเม็ด", p.mg),
+            (true, count) => format!("{} mg ครึ่งเม็ด {} ชิ้น", p.mg, count),
+            (false, count) => format!("{} mg {} เม็ด", p.mg, count),
+        })
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+/// A dated plan as a printable table, one row per day.
+pub(crate) fn plan_table(days: &[PlannedDay], profile: &VkaProfile) -> Table<4> {
+    Table {
+        headers: ["วันที่", "ขนาดยา", "จำนวนเม็ด", "หมายเหตุ"],
+        rows: days
+            .iter()
+            .map(|day| {
+                let class = match day.phase {
+                    PlanPhase::CurrentRegimen | PlanPhase::NewRegimen => None,
+                    PlanPhase::Hold | PlanPhase::Interrupted => Some("stop"),
+                    PlanPhase::Boost => Some("boost"),
+                    PlanPhase::Overlap => Some("overlap"),
+                };
+                (
+                    [
+                        day_label(day.date),
+                        dose_label(day, profile),
+                        tablets_label(&day.pills),
+                        day.notes.join("; "),
+                    ],
+                    class,
+                )
+            })
+            .collect(),
+    }
+}
+
+/// A dated plan as an iCalendar file with one all-day event per day, for
+/// importing into a phone calendar.
+pub(crate) fn plan_calendar(days: &[PlannedDay], profile: &VkaProfile) -> String {
+    let compact = |date: CalendarDate| {
+        let (year, month, day) = date.ymd();
+        format!("{:04}{:02}{:02}", year, month, day)
+    };
+    let escape = |text: &str| {
+        text.replace('\\', "\\\\")
+            .replace(';', "\\;")
+            .replace(',', "\\,")
+            .replace('\n', "\\n")
+    };
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//warfarin-app//TH".to_string(),
+    ];
+    for day in days {
+        let date = compact(day.date);
+        let mut description = vec![tablets_label(&day.pills)];
+        description.extend(day.notes.iter().cloned());
+        description.retain(|part| !part.is_empty());
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}@warfarin-app", date),
+            format!("DTSTAMP:{}T000000Z", date),
+            format!("DTSTART;VALUE=DATE:{}", date),
+            format!("DTEND;VALUE=DATE:{}", compact(day.date.add_days(1))),
+            format!("SUMMARY:{}", escape(&dose_label(day, profile))),
+        ]);
+        if !description.is_empty() {
+            lines.push(format!("DESCRIPTION:{}", escape(&description.join("\n"))));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold_line(line)).collect()
+}
+
+// iCalendar lines are folded at 75 octets, continuing with a leading space.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(week_index: usize, day_index: usize, mg: u8) -> DaySchedule {
+        DaySchedule {
+            day_index,
+            week_index,
+            total_dose: mg as f64,
+            pills: vec![PillRenderData {
+                mg,
+                count: 1,
+                is_half: false,
+            }],
+            is_stop_day: false,
+            is_special_day: false,
+        }
+    }
+
+    #[test]
+    fn reads_a_two_week_cycle_in_order() {
+        let mut schedule: Vec<DaySchedule> = (0..14).map(|d| day(d / 7, d % 7, 3)).collect();
+        schedule[8] = day(1, 1, 2);
+        schedule.reverse();
+        let cycle = regimen_from_schedule(&schedule).unwrap();
+        assert_eq!(cycle.len(), 14);
+        assert_eq!(combo_dose(&cycle[8]), 2.0);
+        assert_eq!(combo_dose(&cycle[1]), 3.0);
+    }
+
+    #[test]
+    fn rejects_a_week_index_beyond_the_longest_cycle() {
+        let mut schedule: Vec<DaySchedule> = (0..7).map(|d| day(0, d, 3)).collect();
+        schedule.push(day(100_000_000, 0, 3));
+        assert!(regimen_from_schedule(&schedule).is_err());
+        schedule.pop();
+        schedule.push(day(CYCLE_WEEKS, 0, 3));
+        assert!(regimen_from_schedule(&schedule).is_err());
+    }
+
+    #[test]
+    fn rejects_missing_days_and_bad_day_index() {
+        let schedule: Vec<DaySchedule> = (0..6).map(|d| day(0, d, 3)).collect();
+        assert!(regimen_from_schedule(&schedule).is_err());
+        assert!(regimen_from_schedule(&[]).is_err());
+        let mut schedule: Vec<DaySchedule> = (0..7).map(|d| day(0, d, 3)).collect();
+        schedule[6].day_index = 7;
+        assert!(regimen_from_schedule(&schedule).is_err());
+    }
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{calculate_total_pills, DosageOption, PillLineSummary};
+
+/// Price of one tablet of a strength, in the hospital's currency.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TabletPrice {
+    pub mg: u8,
+    pub price: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RegimenCost {
+    pub per_week: f64,        // averaged over the whole cycle, before home supply
+    pub per_appointment: f64, // tablets dispensed for the interval, buffer excluded
+}
+
+pub(crate) fn check_prices(prices: &[TabletPrice]) -> Result<(), String> {
+    match prices
+        .iter()
+        .find(|p| !(p.price.is_finite() && p.price >= 0.0))
+    {
+        Some(p) => Err(format!("ราคายา {} mg ไม่ถูกต้อง", p.mg)),
+        None => Ok(()),
+    }
+}
+
+fn line_cost(
+    pill_lines: &[PillLineSummary],
+    prices: &[TabletPrice],
+    count: impl Fn(&PillLineSummary) -> u32,
+) -> Option<f64> {
+    pill_lines
+        .iter()
+        .map(|line| {
+            let price = prices.iter().find(|p| p.mg == line.mg)?.price;
+            Some(count(line) as f64 * price)
+        })
+        .sum()
+}
+
+/// Tablet cost of the regimen averaged per week. `None` without prices or when a
+/// strength the option uses has no price.
+pub(crate) fn weekly_cost(option: &DosageOption, prices: &[TabletPrice]) -> Option<f64> {
+    if prices.is_empty() {
+        return None;
+    }
+    // Two cycles, so an odd half tablet in one cycle pairs with the next.
+    let weeks = option.cycle_weeks() * 2;
+    let cycles = calculate_total_pills(option.daily_combos(0, weeks as u32 * 7), &[]);
+    Some(line_cost(&cycles, prices, |line| line.required_count)? / weeks as f64)
+}
+
+pub(crate) fn regimen_cost(
+    option: &DosageOption,
+    pill_lines: &[PillLineSummary],
+    prices: &[TabletPrice],
+) -> Option<RegimenCost> {
+    Some(RegimenCost {
+        per_week: weekly_cost(option, prices)?,
+        per_appointment: line_cost(pill_lines, prices, |line| line.dispensed_count)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_negative_and_non_finite_prices() {
+        for price in [-1.0, f64::NAN, f64::INFINITY] {
+            assert!(check_prices(&[TabletPrice { mg: 5, price }]).is_err());
+        }
+        assert!(check_prices(&[TabletPrice { mg: 5, price: 0.0 }]).is_ok());
+    }
+}
@@ -0,0 +1,134 @@
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+use crate::{combo_dose, DosageOption, Pill, DAY_NAMES, FLOAT_TOLERANCE};
+
+/// A day of the option's cycle whose tablets differ from the current regimen.
+#[derive(Serialize, Debug, Clone)]
+pub struct ChangedDay {
+    pub week_index: usize,
+    pub day_index: usize,
+    pub current_dose: f64,
+    pub new_dose: f64,
+}
+
+/// How far an option moves from the patient's current regimen.
+#[derive(Serialize, Debug, Clone)]
+pub struct RegimenChange {
+    pub distance: usize, // changed days + strengths added or removed + new half-tablet strengths
+    pub changed_days: Vec<ChangedDay>,
+    pub strengths_added: Vec<u8>,
+    pub strengths_removed: Vec<u8>,
+    pub new_half_strengths: Vec<u8>,
+    pub description: String,
+}
+
+/// Compares `option` with `current`, 7 * N days starting on Monday of week A, day
+/// by day over as many weeks as both cycles need to line up.
+pub(crate) fn regimen_change(option: &DosageOption, current: &[Vec<Pill>]) -> RegimenChange {
+    let option_days = option.cycle_weeks() * 7;
+    let span = lcm(option_days, current.len());
+    let same_tablets = |a: &[Pill], b: &[Pill]| {
+        let mut a = a.to_vec();
+        let mut b = b.to_vec();
+        a.sort();
+        b.sort();
+        a == b
+    };
+
+    let mut changed: BTreeSet<usize> = BTreeSet::new();
+    let mut changed_days = Vec::new();
+    for day in 0..span {
+        let cycle_day = day % option_days;
+        let new_combo = option.combo_for_cycle_day(cycle_day);
+        let current_combo = &current[day % current.len()];
+        if !same_tablets(new_combo, current_combo) && changed.insert(cycle_day) {
+            changed_days.push(ChangedDay {
+                week_index: cycle_day / 7,
+                day_index: cycle_day % 7,
+                current_dose: combo_dose(current_combo),
+                new_dose: combo_dose(new_combo),
+            });
+        }
+    }
+    changed_days.sort_by_key(|d| (d.week_index, d.day_index));
+
+    let option_combos = || (0..option_days).map(|d| option.combo_for_cycle_day(d));
+    let current_combos = || current.iter().map(Vec::as_slice);
+    let new_strengths = strengths_used(option_combos(), false);
+    let new_halves = strengths_used(option_combos(), true);
+    let current_strengths = strengths_used(current_combos(), false);
+    let current_halves = strengths_used(current_combos(), true);
+
+    let strengths_added: Vec<u8> = new_strengths
+        .difference(&current_strengths)
+        .copied()
+        .collect();
+    let strengths_removed: Vec<u8> = current_strengths
+        .difference(&new_strengths)
+        .copied()
+        .collect();
+    let new_half_strengths: Vec<u8> = new_halves.difference(&current_halves).copied().collect();
+
+    let description = if changed_days.is_empty() {
+        "ไม่เปลี่ยนจากเดิม".to_string()
+    } else {
+        let days = changed_days
+            .iter()
+            .map(|d| {
+                let week = if option_days > 7 {
+                    format!("สัปดาห์ {} ", (b'A' + d.week_index as u8) as char)
+                } else {
+                    String::new()
+                };
+                format!(
+                    "{}{} {} → {}",
+                    week,
+                    DAY_NAMES[d.day_index],
+                    dose_label(d.current_dose),
+                    dose_label(d.new_dose)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("เปลี่ยน {} วัน: {}", changed_days.len(), days)
+    };
+
+    RegimenChange {
+        distance: changed_days.len()
+            + strengths_added.len()
+            + strengths_removed.len()
+            + new_half_strengths.len(),
+        changed_days,
+        strengths_added,
+        strengths_removed,
+        new_half_strengths,
+        description,
+    }
+}
+
+fn strengths_used<'a>(combos: impl Iterator<Item = &'a [Pill]>, halves_only: bool) -> BTreeSet<u8> {
+    combos
+        .flatten()
+        .filter(|p| p.count > 0 && (p.half || !halves_only))
+        .map(|p| p.mg)
+        .collect()
+}
+
+fn dose_label(dose: f64) -> String {
+    if dose < FLOAT_TOLERANCE {
+        "หยุดยา".to_string()
+    } else {
+        format!("{:.1} mg", dose)
+    }
+}
+
+fn lcm(a: usize, b: usize) -> usize {
+    let gcd = |mut a: usize, mut b: usize| {
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        a
+    };
+    a / gcd(a, b) * b
+}
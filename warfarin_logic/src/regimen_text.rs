@@ -0,0 +1,930 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+use crate::diagnostics::Rejections;
+use crate::drug::VkaProfile;
+use crate::plan::regimen_from_schedule;
+use crate::{
+    combo_dose, find_comb, render_option, CalculationInput, DaySchedule, DosageOption, FinalOutput,
+    Pill, VkaDrug, CYCLE_WEEKS, DAY_NAMES, DEFAULT_MAX_TABLETS_PER_DAY, FLOAT_TOLERANCE,
+};
+
+/// A regimen as written in the patient's booklet, either free text such as
+/// "5 mg ทุกวัน ยกเว้น ศ. อา. 2.5 mg" or "3 mg M/W/F, 2 mg other days", or
+/// day by day in the `weekly_schedule` shape.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct ParseRegimenInput {
+    #[serde(default)]
+    pub drug: VkaDrug,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub schedule: Vec<DaySchedule>, // used instead of `text` when given
+    #[serde(default)]
+    pub available_pills: Vec<u8>, // for doses written in mg only; defaults to every strength of `drug`
+    #[serde(default)]
+    pub days_until_appointment: u32,
+    #[serde(default)]
+    pub start_day_of_week: u8,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RegimenComplexity {
+    pub half_tablet_strengths: usize,
+    pub tablet_strengths: usize,
+    pub tablets_per_week: u32,
+    pub distinct_daily_doses: usize,
+}
+
+#[derive(Serialize)]
+pub struct ParsedRegimen {
+    pub regimen: FinalOutput,
+    pub complexity: RegimenComplexity,
+    pub unrecognized_words: Vec<String>, // skipped text worth a second look
+}
+
+#[wasm_bindgen]
+pub fn parse_regimen_rust(input_js: JsValue) -> Result<JsValue, JsValue> {
+    let input: ParseRegimenInput =
+        serde_wasm_bindgen::from_value(input_js).map_err(|e| e.to_string())?;
+    Ok(serde_wasm_bindgen::to_value(&parse_regimen(&input)?)?)
+}
+
+/// Reads a written regimen back into the generator's shape: the weekly schedule,
+/// the exact weekly dose, the pill count until the appointment, the safety checks
+/// and the complexity measures used to rank generated options.
+pub fn parse_regimen(input: &ParseRegimenInput) -> Result<ParsedRegimen, String> {
+    let profile = input.drug.profile();
+    let mut available_pills = if input.available_pills.is_empty() {
+        profile.strengths_mg.to_vec()
+    } else {
+        input.available_pills.clone()
+    };
+    available_pills.sort_unstable_by(|a, b| b.cmp(a));
+    profile.check_strengths(&available_pills)?;
+
+    let (cycle, unrecognized_words) = if input.schedule.is_empty() {
+        let (tokens, unrecognized) = tokenize(&input.text);
+        let parser = Parser {
+            profile,
+            available_pills: &available_pills,
+        };
+        (parser.parse(&tokens)?, unrecognized)
+    } else {
+        (regimen_from_schedule(&input.schedule)?, Vec::new())
+    };
+    for combo in &cycle {
+        profile.check_strengths(&combo.iter().map(|p| p.mg).collect::<Vec<_>>())?;
+        let dose = combo_dose(combo);
+        if dose > profile.max_daily_dose_mg + FLOAT_TOLERANCE {
+            return Err(format!(
+                "ขนาดยา {:.1} mg ต่อวันเกินขนาดสูงสุด {} mg/วัน ของ{}",
+                dose, profile.max_daily_dose_mg, profile.name
+            ));
+        }
+    }
+
+    // A week with two or more special doses does not fit the base/special description.
+    let grouped_description = cycle
+        .chunks(7)
+        .any(|week| {
+            distinct_doses(week)
+                .iter()
+                .filter(|&&d| d > FLOAT_TOLERANCE)
+                .count()
+                > 2
+        })
+        .then(|| describe_by_dose(&cycle));
+    let distinct_daily_doses = distinct_doses(&cycle).len();
+
+    let option = DosageOption::from_cycle(cycle);
+    let calculation = CalculationInput {
+        drug: input.drug,
+        weekly_dose: option.weekly_dose_actual,
+        allow_half: true,
+        available_pills,
+        days_until_appointment: input.days_until_appointment,
+        start_day_of_week: input.start_day_of_week,
+        ..Default::default()
+    };
+    let mut regimen = render_option(&option, &calculation);
+    if let Some(description) = grouped_description {
+        regimen.description = description;
+    }
+    Ok(ParsedRegimen {
+        regimen,
+        complexity: RegimenComplexity {
+            half_tablet_strengths: option.half_pill_complexity,
+            tablet_strengths: option.pill_color_count,
+            tablets_per_week: option.total_pill_objects,
+            distinct_daily_doses,
+        },
+        unrecognized_words,
+    })
+}
+
+/// Daily doses of `days`, ascending without repeats.
+fn distinct_doses(days: &[Vec<Pill>]) -> Vec<f64> {
+    let mut doses: Vec<f64> = days.iter().map(|combo| combo_dose(combo)).collect();
+    doses.sort_by(f64::total_cmp);
+    doses.dedup_by(|a, b| (*a - *b).abs() < FLOAT_TOLERANCE);
+    doses
+}
+
+/// "5.0 mg (จ., อ., พ.), 2.5 mg (ศ.), หยุดยา (อา.)" for weeks with more than one
+/// special dose, which the base/special description cannot express.
+fn describe_by_dose(cycle: &[Vec<Pill>]) -> String {
+    let describe_week = |week: &[Vec<Pill>]| {
+        let mut groups: Vec<(f64, Vec<&str>)> = Vec::new();
+        for (day, combo) in week.iter().enumerate() {
+            let dose = combo_dose(combo);
+            match groups
+                .iter_mut()
+                .find(|(d, _)| (d - dose).abs() < FLOAT_TOLERANCE)
+            {
+                Some((_, days)) => days.push(DAY_NAMES[day]),
+                None => groups.push((dose, vec![DAY_NAMES[day]])),
+            }
+        }
+        groups
+            .iter()
+            .map(|(dose, days)| {
+                if *dose < FLOAT_TOLERANCE {
+                    format!("หยุดยา ({})", days.join(", "))
+                } else {
+                    format!("{:.1} mg ({})", dose, days.join(", "))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if cycle.len() == 7 {
+        return describe_week(cycle);
+    }
+    cycle
+        .chunks(7)
+        .enumerate()
+        .map(|(week_idx, week)| {
+            format!(
+                "สัปดาห์ {}: {}",
+                (b'A' + week_idx as u8) as char,
+                describe_week(week)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+// --- Tokenizer ---
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Keyword {
+    Mg,
+    Tablet,
+    Half,
+    Day(usize),
+    Weekdays,
+    Weekend,
+    DayUnit,
+    Range,
+    Every,
+    Except,
+    Others,
+    Stop,
+    Alternate,
+    Start,
+    Week,
+    Filler,
+}
+
+// Matched longest first at any position; ASCII keywords must stand as whole words.
+const KEYWORDS: &[(&str, Keyword)] = &[
+    ("mg", Keyword::Mg),
+    ("มก.", Keyword::Mg),
+    ("มก", Keyword::Mg),
+    ("มิลลิกรัม", Keyword::Mg),
+    ("เม็ด", Keyword::Tablet),
+    ("tab", Keyword::Tablet),
+    ("tabs", Keyword::Tablet),
+    ("tablet", Keyword::Tablet),
+    ("tablets", Keyword::Tablet),
+    ("ครึ่ง", Keyword::Half),
+    ("half", Keyword::Half),
+    ("จ.", Keyword::Day(0)),
+    ("จันทร์", Keyword::Day(0)),
+    ("อ.", Keyword::Day(1)),
+    ("อังคาร", Keyword::Day(1)),
+    ("พ.", Keyword::Day(2)),
+    ("พุธ", Keyword::Day(2)),
+    ("พฤ.", Keyword::Day(3)),
+    ("พฤหัส", Keyword::Day(3)),
+    ("พฤหัสบดี", Keyword::Day(3)),
+    ("ศ.", Keyword::Day(4)),
+    ("ศุกร์", Keyword::Day(4)),
+    ("ส.", Keyword::Day(5)),
+    ("เสาร์", Keyword::Day(5)),
+    ("อา.", Keyword::Day(6)),
+    ("อาทิตย์", Keyword::Day(6)),
+    ("m", Keyword::Day(0)),
+    ("mon", Keyword::Day(0)),
+    ("monday", Keyword::Day(0)),
+    ("mondays", Keyword::Day(0)),
+    ("t", Keyword::Day(1)),
+    ("tu", Keyword::Day(1)),
+    ("tue", Keyword::Day(1)),
+    ("tues", Keyword::Day(1)),
+    ("tuesday", Keyword::Day(1)),
+    ("tuesdays", Keyword::Day(1)),
+    ("w", Keyword::Day(2)),
+    ("wed", Keyword::Day(2)),
+    ("wednesday", Keyword::Day(2)),
+    ("wednesdays", Keyword::Day(2)),
+    ("th", Keyword::Day(3)),
+    ("thu", Keyword::Day(3)),
+    ("thur", Keyword::Day(3)),
+    ("thurs", Keyword::Day(3)),
+    ("thursday", Keyword::Day(3)),
+    ("thursdays", Keyword::Day(3)),
+    ("f", Keyword::Day(4)),
+    ("fri", Keyword::Day(4)),
+    ("friday", Keyword::Day(4)),
+    ("fridays", Keyword::Day(4)),
+    ("sa", Keyword::Day(5)),
+    ("sat", Keyword::Day(5)),
+    ("saturday", Keyword::Day(5)),
+    ("saturdays", Keyword::Day(5)),
+    ("su", Keyword::Day(6)),
+    ("sun", Keyword::Day(6)),
+    ("sunday", Keyword::Day(6)),
+    ("sundays", Keyword::Day(6)),
+    ("weekdays", Keyword::Weekdays),
+    ("weekend", Keyword::Weekend),
+    ("weekends", Keyword::Weekend),
+    ("เสาร์อาทิตย์", Keyword::Weekend),
+    ("สุดสัปดาห์", Keyword::Weekend),
+    ("วัน", Keyword::DayUnit),
+    ("day", Keyword::DayUnit),
+    ("days", Keyword::DayUnit),
+    ("ถึง", Keyword::Range),
+    ("to", Keyword::Range),
+    ("through", Keyword::Range),
+    ("thru", Keyword::Range),
+    ("ทุกวัน", Keyword::Every),
+    ("daily", Keyword::Every),
+    ("every day", Keyword::Every),
+    ("everyday", Keyword::Every),
+    ("ยกเว้น", Keyword::Except),
+    ("except", Keyword::Except),
+    ("วันอื่น", Keyword::Others),
+    ("วันที่เหลือ", Keyword::Others),
+    ("วันธรรมดา", Keyword::Others),
+    ("other days", Keyword::Others),
+    ("all other days", Keyword::Others),
+    ("remaining days", Keyword::Others),
+    ("the rest", Keyword::Others),
+    ("otherwise", Keyword::Others),
+    ("หยุด", Keyword::Stop),
+    ("หยุดยา", Keyword::Stop),
+    ("งด", Keyword::Stop),
+    ("งดยา", Keyword::Stop),
+    ("off", Keyword::Stop),
+    ("stop", Keyword::Stop),
+    ("skip", Keyword::Stop),
+    ("none", Keyword::Stop),
+    ("no dose", Keyword::Stop),
+    ("วันเว้นวัน", Keyword::Alternate),
+    ("สลับ", Keyword::Alternate),
+    ("สลับกับ", Keyword::Alternate),
+    ("alternate", Keyword::Alternate),
+    ("alternating", Keyword::Alternate),
+    ("alternating with", Keyword::Alternate),
+    ("every other day", Keyword::Alternate),
+    ("เริ่ม", Keyword::Start),
+    ("start", Keyword::Start),
+    ("starting", Keyword::Start),
+    ("first", Keyword::Start),
+    ("สัปดาห์", Keyword::Week),
+    ("week", Keyword::Week),
+    ("wk", Keyword::Week),
+    ("วันละ", Keyword::Filler),
+    ("วันพิเศษ", Keyword::Filler),
+    ("ในวันแรก", Keyword::Filler),
+    ("รับประทาน", Keyword::Filler),
+    ("ครั้ง", Keyword::Filler),
+    ("ทาน", Keyword::Filler),
+    ("กิน", Keyword::Filler),
+    ("และ", Keyword::Filler),
+    ("กับ", Keyword::Filler),
+    ("ก่อนนอน", Keyword::Filler),
+    ("and", Keyword::Filler),
+    ("on", Keyword::Filler),
+    ("per", Keyword::Filler),
+    ("take", Keyword::Filler),
+    ("with", Keyword::Filler),
+    ("of", Keyword::Filler),
+    ("at", Keyword::Filler),
+    ("bedtime", Keyword::Filler),
+    ("a", Keyword::Filler),
+    ("x", Keyword::Filler),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Keyword(Keyword),
+    WeekLabel(usize),
+    Plus,
+    Separator, // ";", a new line or " / " between weeks
+}
+
+fn tokenize(text: &str) -> (Vec<Token>, Vec<String>) {
+    let chars: Vec<char> = text.to_lowercase().chars().collect();
+    let mut tokens = Vec::new();
+    let mut unrecognized = Vec::new();
+    let mut word = String::new();
+    let flush = |word: &mut String, unrecognized: &mut Vec<String>| {
+        if !word.is_empty() {
+            unrecognized.push(std::mem::take(word));
+        }
+    };
+
+    let mut i = 0;
+    while i < chars.len() {
+        if let Some((number, len)) = read_number(&chars[i..]) {
+            flush(&mut word, &mut unrecognized);
+            tokens.push(Token::Number(number));
+            i += len;
+            continue;
+        }
+        if let Some((keyword, len)) = match_keyword(&chars, i) {
+            flush(&mut word, &mut unrecognized);
+            i += len;
+            if keyword == Keyword::Week {
+                if let Some((week, len)) = read_week_label(&chars[i..]) {
+                    tokens.push(Token::WeekLabel(week));
+                    i += len;
+                }
+            } else if keyword != Keyword::Filler {
+                tokens.push(Token::Keyword(keyword));
+            }
+            continue;
+        }
+        let c = chars[i];
+        let spaced = |j: usize| chars.get(j).is_none_or(|c| c.is_whitespace());
+        match c {
+            ';' | '\n' => tokens.push(Token::Separator),
+            '/' if i > 0 && spaced(i - 1) && spaced(i + 1) => tokens.push(Token::Separator),
+            '-' | '–' => tokens.push(Token::Keyword(Keyword::Range)),
+            '+' => tokens.push(Token::Plus),
+            c if c.is_ascii_alphabetic() => {
+                flush(&mut word, &mut unrecognized);
+                let len = chars[i..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_alphabetic())
+                    .count();
+                unrecognized.push(chars[i..i + len].iter().collect());
+                i += len;
+                continue;
+            }
+            c if c.is_whitespace() || c.is_ascii_punctuation() || c == 'ๆ' => {}
+            c => {
+                word.push(c);
+                i += 1;
+                continue;
+            }
+        }
+        flush(&mut word, &mut unrecognized);
+        i += 1;
+    }
+    flush(&mut word, &mut unrecognized);
+    (tokens, unrecognized)
+}
+
+/// "5", "2.5", "1/2", "½" or "1½".
+fn read_number(chars: &[char]) -> Option<(f64, usize)> {
+    let digits = |from: usize| {
+        chars[from..]
+            .iter()
+            .take_while(|c| c.is_ascii_digit())
+            .count()
+    };
+    let whole_len = digits(0);
+    if whole_len == 0 {
+        return (chars.first() == Some(&'½')).then_some((0.5, 1));
+    }
+    let mut len = whole_len;
+    let mut text: String = chars[..whole_len].iter().collect();
+    if chars.get(len) == Some(&'.') && digits(len + 1) > 0 {
+        let fraction_len = digits(len + 1);
+        text.extend(&chars[len..len + 1 + fraction_len]);
+        len += 1 + fraction_len;
+    }
+    let mut number: f64 = text.parse().ok()?;
+    if chars.get(len) == Some(&'/') && digits(len + 1) > 0 {
+        let denominator_len = digits(len + 1);
+        let denominator: f64 = chars[len + 1..len + 1 + denominator_len]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .ok()?;
+        if denominator > 0.0 {
+            number /= denominator;
+        }
+        len += 1 + denominator_len;
+    } else if chars.get(len) == Some(&'½') {
+        number += 0.5;
+        len += 1;
+    }
+    Some((number, len))
+}
+
+fn match_keyword(chars: &[char], at: usize) -> Option<(Keyword, usize)> {
+    let is_word_char = |c: Option<&char>| c.is_some_and(|c| c.is_ascii_alphabetic());
+    if at > 0 && is_word_char(chars.get(at - 1)) {
+        return None;
+    }
+    KEYWORDS
+        .iter()
+        .filter_map(|&(word, keyword)| {
+            let len = word.chars().count();
+            let matches =
+                chars.len() >= at + len && word.chars().zip(&chars[at..]).all(|(a, &b)| a == b);
+            let ascii_word = word.ends_with(|c: char| c.is_ascii_alphabetic());
+            (matches && !(ascii_word && is_word_char(chars.get(at + len))))
+                .then_some((keyword, len))
+        })
+        .max_by_key(|&(_, len)| len)
+}
+
+/// "A", "b" or "2" after "week" / "สัปดาห์", as a zero-based week index.
+fn read_week_label(chars: &[char]) -> Option<(usize, usize)> {
+    let spaces = chars.iter().take_while(|c| c.is_whitespace()).count();
+    let label = *chars.get(spaces)?;
+    if chars.get(spaces + 1).is_some_and(|c| c.is_alphanumeric()) {
+        return None;
+    }
+    let week = match label {
+        'a'..='z' => label as usize - 'a' as usize,
+        '1'..='9' => label as usize - '1' as usize,
+        _ => return None,
+    };
+    Some((week, spaces + 1))
+}
+
+// --- Parser ---
+
+/// What one phrase says: a dose for some days, optionally with exceptions.
+#[derive(Default)]
+struct Clause {
+    dose: Option<Vec<Pill>>,
+    days: Vec<usize>,
+    broad: bool, // every day, or every day not given a dose elsewhere
+    except_days: Vec<usize>,
+    except_dose: Option<Vec<Pill>>,
+    in_except: bool,
+    last_was_days: bool,
+}
+
+impl Clause {
+    fn has_scope(&self) -> bool {
+        self.broad || !self.days.is_empty()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.dose.is_none() && !self.has_scope() && !self.in_except
+    }
+}
+
+enum Assignment {
+    Specific(Vec<usize>, Vec<Pill>),
+    Broad(Vec<Pill>),
+    Excluded(Vec<usize>), // "except" days with no dose of their own
+}
+
+struct Parser<'a> {
+    profile: &'static VkaProfile,
+    available_pills: &'a [u8],
+}
+
+impl Parser<'_> {
+    /// The regimen as 7 * N days starting on Monday of week A.
+    fn parse(&self, tokens: &[Token]) -> Result<Vec<Vec<Pill>>, String> {
+        if tokens.contains(&Token::Keyword(Keyword::Alternate)) {
+            return self.parse_alternating(tokens);
+        }
+
+        let mut weeks: Vec<Vec<Assignment>> = vec![Vec::new()];
+        let mut week = 0;
+        let mut clause = Clause::default();
+        let mut i = 0;
+        while i < tokens.len() {
+            match &tokens[i] {
+                Token::Separator => {
+                    self.finish(std::mem::take(&mut clause), &mut weeks[week])?;
+                }
+                Token::WeekLabel(label) => {
+                    self.finish(std::mem::take(&mut clause), &mut weeks[week])?;
+                    week = *label;
+                    if week >= CYCLE_WEEKS {
+                        return Err(format!(
+                            "รองรับตารางยาไม่เกิน {} สัปดาห์ (พบสัปดาห์ {})",
+                            CYCLE_WEEKS,
+                            (b'A' + week as u8) as char
+                        ));
+                    }
+                    if weeks.len() <= week {
+                        weeks.resize_with(week + 1, Vec::new);
+                    }
+                }
+                Token::Number(_) | Token::Keyword(Keyword::Stop) => {
+                    let (dose, len) = self.read_dose(&tokens[i..])?;
+                    i += len;
+                    if let Some(dose) = dose {
+                        if clause.in_except && clause.except_dose.is_none() {
+                            clause.except_dose = Some(dose);
+                        } else if clause.dose.is_none() && !clause.in_except {
+                            clause.dose = Some(dose);
+                        } else if clause.has_scope() || clause.in_except {
+                            self.finish(std::mem::take(&mut clause), &mut weeks[week])?;
+                            clause.dose = Some(dose);
+                        } else {
+                            return Err("พบขนาดยาสองค่าโดยไม่ระบุวัน".to_string());
+                        }
+                        clause.last_was_days = false;
+                    }
+                    continue;
+                }
+                Token::Keyword(Keyword::Day(_) | Keyword::Weekdays | Keyword::Weekend) => {
+                    let (days, len) = read_days(&tokens[i..]);
+                    i += len;
+                    let continues = clause.last_was_days
+                        || (clause.in_except && clause.except_dose.is_none())
+                        || (clause.dose.is_some() && !clause.has_scope())
+                        || clause.dose.is_none();
+                    if !continues {
+                        self.finish(std::mem::take(&mut clause), &mut weeks[week])?;
+                    }
+                    if clause.in_except {
+                        clause.except_days.extend(days);
+                    } else {
+                        clause.days.extend(days);
+                    }
+                    clause.last_was_days = true;
+                    continue;
+                }
+                Token::Keyword(Keyword::Every | Keyword::Others) => {
+                    if clause.dose.is_some() && clause.has_scope() || clause.in_except {
+                        self.finish(std::mem::take(&mut clause), &mut weeks[week])?;
+                    }
+                    clause.broad = true;
+                    clause.last_was_days = false;
+                }
+                Token::Keyword(Keyword::Except) => {
+                    clause.in_except = true;
+                    clause.last_was_days = false;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        self.finish(clause, &mut weeks[week])?;
+
+        let mut cycle = Vec::new();
+        for (week_idx, assignments) in weeks.iter().enumerate() {
+            cycle.extend(resolve_week(assignments, week_idx, weeks.len())?);
+        }
+        Ok(cycle)
+    }
+
+    /// "วันเว้นวัน 3 mg สลับกับ 2 mg" or "5 mg every other day": a 14-day cycle with
+    /// the first dose on Monday of week A.
+    fn parse_alternating(&self, tokens: &[Token]) -> Result<Vec<Vec<Pill>>, String> {
+        let mut doses: Vec<Vec<Pill>> = Vec::new();
+        let mut first: Option<Vec<Pill>> = None;
+        let mut after_start = false;
+        let mut i = 0;
+        while i < tokens.len() {
+            match &tokens[i] {
+                Token::Number(_) | Token::Keyword(Keyword::Stop) => {
+                    let (dose, len) = self.read_dose(&tokens[i..])?;
+                    i += len;
+                    if let Some(dose) = dose {
+                        if after_start {
+                            first = Some(dose);
+                        } else {
+                            doses.push(dose);
+                        }
+                    }
+                    continue;
+                }
+                Token::Keyword(Keyword::Start) => after_start = true,
+                Token::Keyword(Keyword::Day(_) | Keyword::Weekdays | Keyword::Weekend)
+                | Token::WeekLabel(_) => {
+                    return Err("วันเว้นวันไม่สามารถระบุวันในสัปดาห์ร่วมด้วย".to_string());
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        let (mut a, mut b) = match doses.len() {
+            1 => (doses.remove(0), Vec::new()),
+            2 => (doses.remove(0), doses.remove(0)),
+            _ => return Err("วันเว้นวันต้องระบุขนาดยาหนึ่งหรือสองค่า".to_string()),
+        };
+        if first.is_some_and(|first| combo_dose(&first) == combo_dose(&b)) {
+            std::mem::swap(&mut a, &mut b);
+        }
+        Ok((0..14)
+            .map(|day| if day % 2 == 0 { a.clone() } else { b.clone() })
+            .collect())
+    }
+
+    /// A dose starting at `tokens[0]`: "5 mg", "5 mg 1 เม็ด", "3 mg ครึ่งเม็ด",
+    /// "5 mg + 1 mg" or a stop. `None` for a number that is not a dose, such as
+    /// the "2" in "หยุดยา 2 วัน".
+    fn read_dose(&self, tokens: &[Token]) -> Result<(Option<Vec<Pill>>, usize), String> {
+        if tokens[0] == Token::Keyword(Keyword::Stop) {
+            return Ok((Some(Vec::new()), 1));
+        }
+        let mut pills = Vec::new();
+        let mut i = 0;
+        while let Some(Token::Number(number)) = tokens.get(i) {
+            match tokens.get(i + 1) {
+                Some(Token::Keyword(Keyword::Mg)) => {}
+                Some(Token::Keyword(Keyword::DayUnit)) if i == 0 => return Ok((None, 2)),
+                Some(Token::Keyword(Keyword::Tablet)) => {
+                    return Err("ระบุจำนวนเม็ดโดยไม่ระบุขนาดยา (mg)".to_string());
+                }
+                _ if i == 0 => return Ok((None, 1)),
+                _ => break,
+            }
+            i += 2;
+            let tablets = match (tokens.get(i), tokens.get(i + 1)) {
+                (Some(Token::Number(count)), Some(Token::Keyword(Keyword::Tablet))) => {
+                    i += 2;
+                    Some(*count)
+                }
+                (Some(Token::Keyword(Keyword::Half)), Some(Token::Keyword(Keyword::Tablet))) => {
+                    i += 2;
+                    Some(0.5)
+                }
+                (Some(Token::Keyword(Keyword::Tablet)), _) => {
+                    i += 1;
+                    Some(1.0)
+                }
+                _ => None,
+            };
+            match tablets {
+                Some(count) => pills.extend(self.tablets(*number, count)?),
+                None => pills.extend(self.combo_for_dose(*number)?),
+            }
+            // Doses given tablet by tablet run on; "+" joins any two.
+            match tokens.get(i) {
+                Some(Token::Plus) => i += 1,
+                Some(Token::Number(_)) if tablets.is_some() => {}
+                _ => break,
+            }
+        }
+        Ok((Some(merge_pills(pills)?), i))
+    }
+
+    fn tablets(&self, mg: f64, count: f64) -> Result<Vec<Pill>, String> {
+        let strength = mg.round() as u8;
+        if (mg - strength as f64).abs() > FLOAT_TOLERANCE
+            || !self.profile.strengths_mg.contains(&strength)
+        {
+            return Err(format!("{}ไม่มีขนาด {} mg", self.profile.name, mg));
+        }
+        let halves = count * 2.0;
+        if (halves - halves.round()).abs() > FLOAT_TOLERANCE || halves < 0.0 {
+            return Err(format!("จำนวนเม็ดยา {} ไม่ถูกต้อง", count));
+        }
+        let halves = u8::try_from(halves.round() as i64)
+            .map_err(|_| format!("จำนวนเม็ดยา {} ไม่ถูกต้อง", count))?;
+        let mut pills = Vec::new();
+        if halves / 2 > 0 {
+            pills.push(Pill {
+                mg: strength,
+                count: halves / 2,
+                half: false,
+            });
+        }
+        if halves % 2 == 1 {
+            pills.push(Pill {
+                mg: strength,
+                count: 1,
+                half: true,
+            });
+        }
+        Ok(pills)
+    }
+
+    /// The simplest tablets for a dose written in mg: whole tablets first, then
+    /// the fewest tablets and strengths.
+    fn combo_for_dose(&self, dose: f64) -> Result<Vec<Pill>, String> {
+        if dose < FLOAT_TOLERANCE {
+            return Ok(Vec::new());
+        }
+        find_comb(
+            dose,
+            self.available_pills,
+            true,
+            1,
+            DEFAULT_MAX_TABLETS_PER_DAY,
+            &mut Rejections::default(),
+        )
+        .into_iter()
+        .min_by_key(|combo| {
+            (
+                combo.iter().any(|p| p.half),
+                combo.iter().map(|p| p.count).sum::<u8>(),
+                combo.len(),
+            )
+        })
+        .ok_or_else(|| format!("ไม่สามารถจัดยา {:.1} mg ด้วยเม็ดยาที่มี", dose))
+    }
+
+    fn finish(&self, clause: Clause, assignments: &mut Vec<Assignment>) -> Result<(), String> {
+        if clause.is_empty() {
+            return Ok(());
+        }
+        let specific = clause.has_scope() && !clause.broad;
+        let Some(dose) = clause.dose else {
+            let days = clause
+                .days
+                .iter()
+                .map(|&d| DAY_NAMES[d])
+                .collect::<Vec<_>>();
+            return Err(format!("ไม่ได้ระบุขนาดยาสำหรับ {}", days.join(", ")));
+        };
+        if clause.in_except && clause.except_days.is_empty() {
+            return Err("ไม่ได้ระบุวันหลังคำว่ายกเว้น".to_string());
+        }
+        if specific {
+            let days = clause
+                .days
+                .iter()
+                .copied()
+                .filter(|d| !clause.except_days.contains(d))
+                .collect();
+            assignments.push(Assignment::Specific(days, dose));
+        } else {
+            assignments.push(Assignment::Broad(dose));
+        }
+        match clause.except_dose {
+            Some(except_dose) => {
+                assignments.push(Assignment::Specific(clause.except_days, except_dose))
+            }
+            None if !clause.except_days.is_empty() => {
+                assignments.push(Assignment::Excluded(clause.except_days))
+            }
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+/// Days starting at `tokens[0]`, expanding ranges such as "จ.-ศ." or "Mon to Fri".
+fn read_days(tokens: &[Token]) -> (Vec<usize>, usize) {
+    let day_set = |token: &Token| match token {
+        Token::Keyword(Keyword::Day(day)) => Some(vec![*day]),
+        Token::Keyword(Keyword::Weekdays) => Some((0..5).collect()),
+        Token::Keyword(Keyword::Weekend) => Some(vec![5, 6]),
+        _ => None,
+    };
+    let mut days = day_set(&tokens[0]).unwrap_or_default();
+    let mut len = 1;
+    if let (Some(Token::Keyword(Keyword::Range)), Some(Token::Keyword(Keyword::Day(last)))) =
+        (tokens.get(1), tokens.get(2))
+    {
+        let first = days[0];
+        days = (0..7)
+            .map(|d| (first + d) % 7)
+            .take((last + 7 - first) % 7 + 1)
+            .collect();
+        len = 3;
+    }
+    (days, len)
+}
+
+fn merge_pills(pills: Vec<Pill>) -> Result<Vec<Pill>, String> {
+    let mut counts: HashMap<(u8, bool), u8> = HashMap::new();
+    for pill in pills {
+        let count = counts.entry((pill.mg, pill.half)).or_insert(0);
+        *count = count
+            .checked_add(pill.count)
+            .ok_or_else(|| format!("จำนวนเม็ดยา {} mg มากเกินไป", pill.mg))?;
+    }
+    let mut merged: Vec<Pill> = counts
+        .into_iter()
+        .map(|((mg, half), count)| Pill { mg, count, half })
+        .collect();
+    merged.sort();
+    Ok(merged)
+}
+
+/// One week of daily combos: days named explicitly win over "every day" and
+/// "other days"; "except" days with no dose of their own are stop days.
+fn resolve_week(
+    assignments: &[Assignment],
+    week_idx: usize,
+    weeks: usize,
+) -> Result<Vec<Vec<Pill>>, String> {
+    let week_name = if weeks > 1 {
+        format!("สัปดาห์ {} ", (b'A' + week_idx as u8) as char)
+    } else {
+        String::new()
+    };
+    let broad: Vec<&Vec<Pill>> = assignments
+        .iter()
+        .filter_map(|a| match a {
+            Assignment::Broad(dose) => Some(dose),
+            _ => None,
+        })
+        .collect();
+    if broad
+        .windows(2)
+        .any(|pair| combo_dose(pair[0]) != combo_dose(pair[1]))
+    {
+        return Err(format!("{}ระบุขนาดยาทุกวันมากกว่าหนึ่งค่า", week_name));
+    }
+
+    (0..7)
+        .map(|day| {
+            let mut specific = assignments.iter().filter_map(|a| match a {
+                Assignment::Specific(days, dose) if days.contains(&day) => Some(dose),
+                _ => None,
+            });
+            if let Some(dose) = specific.next() {
+                if specific.any(|other| combo_dose(other) != combo_dose(dose)) {
+                    return Err(format!("{}ระบุขนาดยาวัน {} ซ้ำกัน", week_name, DAY_NAMES[day]));
+                }
+                return Ok(dose.clone());
+            }
+            let excluded = assignments
+                .iter()
+                .any(|a| matches!(a, Assignment::Excluded(days) if days.contains(&day)));
+            match broad.first() {
+                _ if excluded => Ok(Vec::new()),
+                Some(dose) => Ok((*dose).clone()),
+                None => Err(format!("{}ไม่ได้ระบุขนาดยาวัน {}", week_name, DAY_NAMES[day])),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<ParsedRegimen, String> {
+        parse_regimen(&ParseRegimenInput {
+            text: text.to_string(),
+            days_until_appointment: 28,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn reads_a_base_dose_with_exceptions() {
+        let parsed = parse("5 mg ทุกวัน ยกเว้น ศ. อา. 2.5 mg").unwrap();
+        assert_eq!(parsed.regimen.weekly_dose_actual, 30.0);
+        let schedule = &parsed.regimen.weekly_schedule;
+        let friday = schedule.iter().find(|d| d.day_index == 4).unwrap();
+        let monday = schedule.iter().find(|d| d.day_index == 0).unwrap();
+        assert_eq!((friday.total_dose, monday.total_dose), (2.5, 5.0));
+        assert!(parsed.unrecognized_words.is_empty());
+    }
+
+    #[test]
+    fn reads_english_day_lists() {
+        let parsed = parse("3 mg M/W/F, 2 mg other days").unwrap();
+        assert_eq!(parsed.regimen.weekly_dose_actual, 17.0);
+    }
+
+    #[test]
+    fn reads_two_week_cycles() {
+        let parsed = parse("สัปดาห์ A 3 mg ทุกวัน / สัปดาห์ B 2 mg ทุกวัน").unwrap();
+        assert_eq!(parsed.regimen.cycle_weeks, 2);
+        assert_eq!(parsed.regimen.weekly_dose_actual, 17.5);
+    }
+
+    #[test]
+    fn rejects_weeks_beyond_the_longest_cycle() {
+        assert!(parse("สัปดาห์ A 3 mg ทุกวัน / สัปดาห์ C 2 mg ทุกวัน").is_err());
+        assert!(parse("week 3 2 mg daily").is_err_and(|e| e.contains("สัปดาห์ C")));
+    }
+
+    #[test]
+    fn rejects_tablet_counts_that_overflow() {
+        assert!(parse("5 mg 100 เม็ด + 5 mg 100 เม็ด + 5 mg 100 เม็ด").is_err());
+        assert!(parse("5 mg 300 เม็ด").is_err());
+    }
+
+    #[test]
+    fn rejects_a_day_above_the_daily_maximum() {
+        assert!(parse("5 mg 4 เม็ด ทุกวัน").is_err());
+        assert!(parse("5 mg 3 เม็ด ทุกวัน").is_ok());
+    }
+}
@@ -0,0 +1,342 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+// Major bleeds per 100 patient-years by HAS-BLED score (5 and above pooled).
+const HAS_BLED_BLEEDING_RATES: [f64; 6] = [1.13, 1.02, 1.88, 3.74, 8.70, 12.50];
+// Adjusted strokes per 100 patient-years by CHA2DS2-VASc score.
+const CHA2DS2_VASC_STROKE_RATES: [f64; 10] = [0.0, 1.3, 2.2, 3.2, 4.0, 6.7, 9.8, 9.6, 6.7, 15.2];
+// Major haemorrhages per 100 patient-years for ATRIA low, intermediate and high risk.
+const ATRIA_BLEEDING_RATES: [f64; 3] = [0.76, 2.62, 5.76];
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Sex {
+    Male,
+    Female,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+pub enum RiskCategory {
+    Low,
+    Moderate,
+    High,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct HasBledInput {
+    pub age: u32,
+    pub uncontrolled_hypertension: bool, // systolic > 160 mmHg
+    pub abnormal_renal_function: bool,   // dialysis, transplant or creatinine ≥ 2.26 mg/dL
+    pub abnormal_liver_function: bool,   // cirrhosis, or bilirubin > 2× and AST/ALT > 3× ULN
+    pub stroke: bool,
+    pub bleeding_history: bool,
+    pub labile_inr: bool, // TTR below 60%
+    pub antiplatelet_or_nsaid: bool,
+    pub alcohol_excess: bool, // 8 or more drinks a week
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Cha2ds2VascInput {
+    pub age: u32,
+    pub sex: Sex,
+    #[serde(default)]
+    pub congestive_heart_failure: bool,
+    #[serde(default)]
+    pub hypertension: bool,
+    #[serde(default)]
+    pub diabetes: bool,
+    #[serde(default)]
+    pub stroke_tia_thromboembolism: bool,
+    #[serde(default)]
+    pub vascular_disease: bool, // prior MI, peripheral artery disease or aortic plaque
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct AtriaInput {
+    pub age: u32,
+    pub anemia: bool,               // Hb < 13 g/dL in men, < 12 g/dL in women
+    pub severe_renal_disease: bool, // eGFR < 30 mL/min or dialysis
+    pub prior_hemorrhage: bool,
+    pub hypertension: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RiskScore {
+    pub score: u32,
+    pub category: RiskCategory,
+    pub annual_event_rate_percent: f64,
+    pub description: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct RiskScoreInput {
+    pub has_bled: Option<HasBledInput>,
+    pub cha2ds2_vasc: Option<Cha2ds2VascInput>,
+    pub atria: Option<AtriaInput>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RiskScores {
+    pub has_bled: Option<RiskScore>,
+    pub cha2ds2_vasc: Option<RiskScore>,
+    pub atria: Option<RiskScore>,
+}
+
+#[wasm_bindgen]
+pub fn calculate_risk_scores_rust(input_js: JsValue) -> Result<JsValue, JsValue> {
+    let input: RiskScoreInput =
+        serde_wasm_bindgen::from_value(input_js).map_err(|e| e.to_string())?;
+    Ok(serde_wasm_bindgen::to_value(&calculate_risk_scores(
+        &input,
+    ))?)
+}
+
+/// Scores whichever of the three calculators the input fills in.
+pub fn calculate_risk_scores(input: &RiskScoreInput) -> RiskScores {
+    RiskScores {
+        has_bled: input.has_bled.as_ref().map(score_has_bled),
+        cha2ds2_vasc: input.cha2ds2_vasc.as_ref().map(score_cha2ds2_vasc),
+        atria: input.atria.as_ref().map(score_atria),
+    }
+}
+
+pub fn score_has_bled(input: &HasBledInput) -> RiskScore {
+    let score = [
+        input.uncontrolled_hypertension,
+        input.abnormal_renal_function,
+        input.abnormal_liver_function,
+        input.stroke,
+        input.bleeding_history,
+        input.labile_inr,
+        input.age > 65,
+        input.antiplatelet_or_nsaid,
+        input.alcohol_excess,
+    ]
+    .iter()
+    .filter(|&&present| present)
+    .count() as u32;
+    let category = match score {
+        0 => RiskCategory::Low,
+        1..=2 => RiskCategory::Moderate,
+        _ => RiskCategory::High,
+    };
+    let rate = HAS_BLED_BLEEDING_RATES[(score as usize).min(HAS_BLED_BLEEDING_RATES.len() - 1)];
+    RiskScore {
+        score,
+        category,
+        annual_event_rate_percent: rate,
+        description: format!(
+            "HAS-BLED {} คะแนน: {} (เลือดออกรุนแรง {}% ต่อปี)",
+            score,
+            category_label(category, "เสี่ยงเลือดออก"),
+            rate
+        ),
+    }
+}
+
+pub fn score_cha2ds2_vasc(input: &Cha2ds2VascInput) -> RiskScore {
+    let age_points = if input.age >= 75 {
+        2
+    } else if input.age >= 65 {
+        1
+    } else {
+        0
+    };
+    let score = age_points
+        + u32::from(input.congestive_heart_failure)
+        + u32::from(input.hypertension)
+        + u32::from(input.diabetes)
+        + 2 * u32::from(input.stroke_tia_thromboembolism)
+        + u32::from(input.vascular_disease)
+        + u32::from(input.sex == Sex::Female);
+    // Female sex is a risk modifier rather than a risk factor on its own.
+    let risk_factor_points = score - u32::from(input.sex == Sex::Female);
+    let category = match risk_factor_points {
+        0 => RiskCategory::Low,
+        1 => RiskCategory::Moderate,
+        _ => RiskCategory::High,
+    };
+    let rate = CHA2DS2_VASC_STROKE_RATES[score as usize];
+    RiskScore {
+        score,
+        category,
+        annual_event_rate_percent: rate,
+        description: format!(
+            "CHA2DS2-VASc {} คะแนน: {} (โรคหลอดเลือดสมอง {}% ต่อปี)",
+            score,
+            category_label(category, "เสี่ยงหลอดเลือดสมอง"),
+            rate
+        ),
+    }
+}
+
+pub fn score_atria(input: &AtriaInput) -> RiskScore {
+    let score = 3 * u32::from(input.anemia)
+        + 3 * u32::from(input.severe_renal_disease)
+        + if input.age >= 75 { 2 } else { 0 }
+        + u32::from(input.prior_hemorrhage)
+        + u32::from(input.hypertension);
+    let category = match score {
+        0..=3 => RiskCategory::Low,
+        4 => RiskCategory::Moderate,
+        _ => RiskCategory::High,
+    };
+    let rate = ATRIA_BLEEDING_RATES[category as usize];
+    RiskScore {
+        score,
+        category,
+        annual_event_rate_percent: rate,
+        description: format!(
+            "ATRIA {} คะแนน: {} (เลือดออกรุนแรง {}% ต่อปี)",
+            score,
+            category_label(category, "เสี่ยงเลือดออก"),
+            rate
+        ),
+    }
+}
+
+fn category_label(category: RiskCategory, risk: &str) -> String {
+    let level = match category {
+        RiskCategory::Low => "ต่ำ",
+        RiskCategory::Moderate => "ปานกลาง",
+        RiskCategory::High => "สูง",
+    };
+    format!("{}{}", risk, level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // HAS-BLED with the first `factors` non-age risk factors present.
+    fn has_bled(factors: usize) -> RiskScore {
+        let mut present = [false; 8];
+        present[..factors].fill(true);
+        score_has_bled(&HasBledInput {
+            age: 60,
+            uncontrolled_hypertension: present[0],
+            abnormal_renal_function: present[1],
+            abnormal_liver_function: present[2],
+            stroke: present[3],
+            bleeding_history: present[4],
+            labile_inr: present[5],
+            antiplatelet_or_nsaid: present[6],
+            alcohol_excess: present[7],
+        })
+    }
+
+    fn cha2ds2_vasc(age: u32, sex: Sex) -> Cha2ds2VascInput {
+        Cha2ds2VascInput {
+            age,
+            sex,
+            congestive_heart_failure: false,
+            hypertension: false,
+            diabetes: false,
+            stroke_tia_thromboembolism: false,
+            vascular_disease: false,
+        }
+    }
+
+    #[test]
+    fn has_bled_categories() {
+        for (factors, category, rate) in [
+            (0, RiskCategory::Low, 1.13),
+            (1, RiskCategory::Moderate, 1.02),
+            (2, RiskCategory::Moderate, 1.88),
+            (3, RiskCategory::High, 3.74),
+            (4, RiskCategory::High, 8.70),
+        ] {
+            let score = has_bled(factors);
+            assert_eq!(score.score, factors as u32);
+            assert_eq!(score.category, category);
+            assert_eq!(score.annual_event_rate_percent, rate);
+        }
+    }
+
+    #[test]
+    fn has_bled_5_and_above_uses_the_pooled_rate() {
+        assert_eq!(has_bled(5).annual_event_rate_percent, 12.50);
+        assert_eq!(has_bled(8).annual_event_rate_percent, 12.50);
+    }
+
+    #[test]
+    fn has_bled_counts_age_above_65() {
+        let at = |age| {
+            score_has_bled(&HasBledInput {
+                age,
+                ..Default::default()
+            })
+            .score
+        };
+        assert_eq!(at(65), 0);
+        assert_eq!(at(66), 1);
+    }
+
+    #[test]
+    fn cha2ds2_vasc_female_sex_alone_is_low() {
+        let score = score_cha2ds2_vasc(&cha2ds2_vasc(50, Sex::Female));
+        assert_eq!((score.score, score.category), (1, RiskCategory::Low));
+        let score = score_cha2ds2_vasc(&cha2ds2_vasc(50, Sex::Male));
+        assert_eq!((score.score, score.category), (0, RiskCategory::Low));
+        assert_eq!(score.annual_event_rate_percent, 0.0);
+    }
+
+    #[test]
+    fn cha2ds2_vasc_categories() {
+        let score = score_cha2ds2_vasc(&cha2ds2_vasc(65, Sex::Male));
+        assert_eq!((score.score, score.category), (1, RiskCategory::Moderate));
+        let score = score_cha2ds2_vasc(&cha2ds2_vasc(65, Sex::Female));
+        assert_eq!((score.score, score.category), (2, RiskCategory::Moderate));
+        let score = score_cha2ds2_vasc(&cha2ds2_vasc(75, Sex::Male));
+        assert_eq!((score.score, score.category), (2, RiskCategory::High));
+        let score = score_cha2ds2_vasc(&Cha2ds2VascInput {
+            stroke_tia_thromboembolism: true,
+            ..cha2ds2_vasc(64, Sex::Male)
+        });
+        assert_eq!((score.score, score.category), (2, RiskCategory::High));
+    }
+
+    #[test]
+    fn cha2ds2_vasc_maximum_is_9() {
+        let score = score_cha2ds2_vasc(&Cha2ds2VascInput {
+            age: 80,
+            sex: Sex::Female,
+            congestive_heart_failure: true,
+            hypertension: true,
+            diabetes: true,
+            stroke_tia_thromboembolism: true,
+            vascular_disease: true,
+        });
+        assert_eq!(score.score, 9);
+        assert_eq!(score.annual_event_rate_percent, 15.2);
+    }
+
+    #[test]
+    fn atria_categories() {
+        let score = score_atria(&AtriaInput::default());
+        assert_eq!((score.score, score.category), (0, RiskCategory::Low));
+        let score = score_atria(&AtriaInput {
+            anemia: true,
+            ..Default::default()
+        });
+        assert_eq!((score.score, score.category), (3, RiskCategory::Low));
+        let score = score_atria(&AtriaInput {
+            anemia: true,
+            hypertension: true,
+            ..Default::default()
+        });
+        assert_eq!((score.score, score.category), (4, RiskCategory::Moderate));
+        assert_eq!(score.annual_event_rate_percent, 2.62);
+        let score = score_atria(&AtriaInput {
+            age: 75,
+            severe_renal_disease: true,
+            ..Default::default()
+        });
+        assert_eq!((score.score, score.category), (5, RiskCategory::High));
+        assert_eq!(score.annual_event_rate_percent, 5.76);
+    }
+}
@@ -0,0 +1,371 @@
+use serde::{Deserialize, Serialize};
+
+use crate::dose_response::InrPrediction;
+use crate::indication::InrTarget;
+use crate::risk_scores::RiskCategory;
+use crate::{combo_dose, CalculationInput, DosageOption, FLOAT_TOLERANCE};
+
+/// Thresholds for the rule-based checks run on every generated option.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct SafetyLimits {
+    pub max_day_to_day_change_mg: f64,
+    pub max_daily_dose_mg: f64,
+    pub max_weekly_change_percent: f64,
+    pub max_consecutive_stop_days: usize,
+    pub max_tablets_per_day: u32,
+}
+
+impl Default for SafetyLimits {
+    fn default() -> Self {
+        SafetyLimits {
+            max_day_to_day_change_mg: 5.0,
+            max_daily_dose_mg: 10.0,
+            max_weekly_change_percent: 20.0,
+            max_consecutive_stop_days: 1,
+            max_tablets_per_day: 3,
+        }
+    }
+}
+
+impl SafetyLimits {
+    /// Caps the weekly dose change for patients at moderate or high bleeding risk.
+    pub fn for_bleeding_risk(&self, category: RiskCategory) -> SafetyLimits {
+        let max_weekly_change_percent = match category {
+            RiskCategory::Low => self.max_weekly_change_percent,
+            RiskCategory::Moderate => self.max_weekly_change_percent.min(15.0),
+            RiskCategory::High => self.max_weekly_change_percent.min(10.0),
+        };
+        SafetyLimits {
+            max_weekly_change_percent,
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SafetyRule {
+    DayToDaySwing,
+    DailyDoseCeiling,
+    WeeklyDoseChange,
+    ConsecutiveStopDays,
+    PillBurden,
+    PredictedInrOutOfRange,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SafetyWarning {
+    pub rule: SafetyRule,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Checks one option against `input.safety`, most severe warnings first.
+pub(crate) fn check_safety(option: &DosageOption, input: &CalculationInput) -> Vec<SafetyWarning> {
+    let limits = &match input.bleeding_risk {
+        Some(category) => input.safety.for_bleeding_risk(category),
+        None => input.safety.clone(),
+    };
+    let cycle_days = option.cycle_weeks() * 7;
+    let doses: Vec<f64> = (0..cycle_days)
+        .map(|day| combo_dose(option.combo_for_cycle_day(day)))
+        .collect();
+    let mut warnings = Vec::new();
+
+    let max_swing = (0..cycle_days)
+        .map(|day| (doses[day] - doses[(day + 1) % cycle_days]).abs())
+        .fold(0.0, f64::max);
+    if max_swing > limits.max_day_to_day_change_mg + FLOAT_TOLERANCE {
+        warnings.push(SafetyWarning {
+            rule: SafetyRule::DayToDaySwing,
+            severity: Severity::Warning,
+            message: format!(
+                "ขนาดยาวันติดกันต่างกันถึง {:.1} mg (เกิน {:.1} mg)",
+                max_swing, limits.max_day_to_day_change_mg
+            ),
+        });
+    }
+
+    let max_daily_dose = doses.iter().copied().fold(0.0, f64::max);
+    if max_daily_dose > limits.max_daily_dose_mg + FLOAT_TOLERANCE {
+        warnings.push(SafetyWarning {
+            rule: SafetyRule::DailyDoseCeiling,
+            severity: Severity::Warning,
+            message: format!(
+                "ขนาดยาสูงสุด {:.1} mg/วัน เกินเพดาน {:.1} mg/วัน",
+                max_daily_dose, limits.max_daily_dose_mg
+            ),
+        });
+    }
+
+    if let Some(previous) = input.previous_weekly_dose.filter(|&dose| dose > 0.0) {
+        let change_percent = (option.weekly_dose_actual - previous) / previous * 100.0;
+        if change_percent.abs() > limits.max_weekly_change_percent + FLOAT_TOLERANCE {
+            let severity = if change_percent.abs() > limits.max_weekly_change_percent * 2.0 {
+                Severity::Critical
+            } else {
+                Severity::Warning
+            };
+            warnings.push(SafetyWarning {
+                rule: SafetyRule::WeeklyDoseChange,
+                severity,
+                message: format!(
+                    "ขนาดยาต่อสัปดาห์เปลี่ยน {:+.0}% จากเดิม {:.1} mg (เกิน {:.0}%)",
+                    change_percent, previous, limits.max_weekly_change_percent
+                ),
+            });
+        }
+    }
+
+    let stop_run = longest_stop_run(&doses);
+    if stop_run > limits.max_consecutive_stop_days && stop_run < cycle_days {
+        let severity = if stop_run > limits.max_consecutive_stop_days + 1 {
+            Severity::Critical
+        } else {
+            Severity::Warning
+        };
+        warnings.push(SafetyWarning {
+            rule: SafetyRule::ConsecutiveStopDays,
+            severity,
+            message: format!("หยุดยาติดต่อกัน {} วัน", stop_run),
+        });
+    }
+
+    let max_tablets = (0..cycle_days)
+        .map(|day| {
+            option
+                .combo_for_cycle_day(day)
+                .iter()
+                .map(|p| p.count as u32)
+                .sum::<u32>()
+        })
+        .max()
+        .unwrap_or(0);
+    if max_tablets > limits.max_tablets_per_day {
+        warnings.push(SafetyWarning {
+            rule: SafetyRule::PillBurden,
+            severity: Severity::Info,
+            message: format!("บางวันต้องรับประทานยา {} เม็ด", max_tablets),
+        });
+    }
+
+    warnings.sort_by_key(|w| std::cmp::Reverse(w.severity));
+    warnings
+}
+
+/// Longest run of zero-dose days, wrapping around the end of the cycle.
+fn longest_stop_run(doses: &[f64]) -> usize {
+    let is_stop = |day: usize| doses[day % doses.len()] < FLOAT_TOLERANCE;
+    let mut longest = 0;
+    let mut run = 0;
+    for day in 0..doses.len() * 2 {
+        run = if is_stop(day) { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    longest.min(doses.len())
+}
+
+/// Flags an option whose predicted INR falls outside the patient's target range.
+pub(crate) fn check_predicted_inr(
+    prediction: &InrPrediction,
+    target: &InrTarget,
+) -> Option<SafetyWarning> {
+    let inr = prediction.predicted_inr;
+    if inr >= target.low && inr <= target.high {
+        return None;
+    }
+    let direction = if inr < target.low {
+        "ต่ำกว่า"
+    } else {
+        "สูงกว่า"
+    };
+    Some(SafetyWarning {
+        rule: SafetyRule::PredictedInrOutOfRange,
+        // An extrapolated prediction is too uncertain to warn about strongly.
+        severity: if prediction.extrapolated {
+            Severity::Info
+        } else {
+            Severity::Warning
+        },
+        message: format!(
+            "INR ที่คาดการณ์ {:.2} {}ช่วงเป้าหมาย {:.1}–{:.1}",
+            inr, direction, target.low, target.high
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OptionType, Pill};
+
+    // One week of doses built from 1 mg tablets, Monday first.
+    fn option(doses: [u8; 7]) -> DosageOption {
+        let week: Vec<Vec<Pill>> = doses
+            .iter()
+            .map(|&mg| match mg {
+                0 => Vec::new(),
+                _ => vec![Pill {
+                    mg: 1,
+                    count: mg,
+                    half: false,
+                }],
+            })
+            .collect();
+        let weekly_dose = doses.iter().map(|&mg| mg as f64).sum();
+        DosageOption::new(OptionType::NonUniform(week), weekly_dose)
+    }
+
+    fn input() -> CalculationInput {
+        CalculationInput {
+            safety: SafetyLimits {
+                max_tablets_per_day: 20,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn find(warnings: &[SafetyWarning], rule: SafetyRule) -> Option<Severity> {
+        warnings.iter().find(|w| w.rule == rule).map(|w| w.severity)
+    }
+
+    #[test]
+    fn day_to_day_swing_wraps_from_sunday_to_monday() {
+        let warnings = check_safety(&option([10, 7, 5, 5, 5, 5, 4]), &input());
+        assert_eq!(
+            find(&warnings, SafetyRule::DayToDaySwing),
+            Some(Severity::Warning)
+        );
+        let warnings = check_safety(&option([10, 7, 5, 5, 5, 5, 6]), &input());
+        assert_eq!(find(&warnings, SafetyRule::DayToDaySwing), None);
+    }
+
+    #[test]
+    fn daily_dose_above_the_ceiling() {
+        let warnings = check_safety(&option([11; 7]), &input());
+        assert_eq!(
+            find(&warnings, SafetyRule::DailyDoseCeiling),
+            Some(Severity::Warning)
+        );
+        let warnings = check_safety(&option([10; 7]), &input());
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn weekly_change_escalates_past_twice_the_limit() {
+        let input = CalculationInput {
+            previous_weekly_dose: Some(40.0),
+            ..input()
+        };
+        // 42 mg is +5%, 49 mg +22.5%, 63 mg +57.5% against a 20% limit.
+        let severity = |doses| {
+            find(
+                &check_safety(&option(doses), &input),
+                SafetyRule::WeeklyDoseChange,
+            )
+        };
+        assert_eq!(severity([6; 7]), None);
+        assert_eq!(severity([7; 7]), Some(Severity::Warning));
+        assert_eq!(severity([9; 7]), Some(Severity::Critical));
+    }
+
+    #[test]
+    fn bleeding_risk_tightens_the_weekly_change_limit() {
+        let limits = SafetyLimits::default();
+        assert_eq!(
+            limits
+                .for_bleeding_risk(RiskCategory::Low)
+                .max_weekly_change_percent,
+            20.0
+        );
+        assert_eq!(
+            limits
+                .for_bleeding_risk(RiskCategory::Moderate)
+                .max_weekly_change_percent,
+            15.0
+        );
+        assert_eq!(
+            limits
+                .for_bleeding_risk(RiskCategory::High)
+                .max_weekly_change_percent,
+            10.0
+        );
+
+        // +22.5% is a warning against 20% but critical against 10%.
+        let input = CalculationInput {
+            previous_weekly_dose: Some(40.0),
+            bleeding_risk: Some(RiskCategory::High),
+            ..input()
+        };
+        let warnings = check_safety(&option([7; 7]), &input);
+        assert_eq!(
+            find(&warnings, SafetyRule::WeeklyDoseChange),
+            Some(Severity::Critical)
+        );
+    }
+
+    #[test]
+    fn stop_runs_wrap_across_the_cycle_boundary() {
+        assert_eq!(longest_stop_run(&[0.0, 5.0, 5.0, 5.0, 5.0, 5.0, 0.0]), 2);
+        assert_eq!(longest_stop_run(&[0.0; 7]), 7);
+
+        let warnings = check_safety(&option([0, 5, 5, 5, 5, 5, 0]), &input());
+        assert_eq!(
+            find(&warnings, SafetyRule::ConsecutiveStopDays),
+            Some(Severity::Warning)
+        );
+        let warnings = check_safety(&option([0, 5, 5, 5, 5, 0, 0]), &input());
+        assert_eq!(
+            find(&warnings, SafetyRule::ConsecutiveStopDays),
+            Some(Severity::Critical)
+        );
+        let warnings = check_safety(&option([0, 5, 5, 0, 5, 5, 5]), &input());
+        assert_eq!(find(&warnings, SafetyRule::ConsecutiveStopDays), None);
+    }
+
+    #[test]
+    fn pill_burden_is_informational_and_sorted_last() {
+        let input = CalculationInput {
+            previous_weekly_dose: Some(15.0),
+            safety: SafetyLimits::default(),
+            ..Default::default()
+        };
+        let warnings = check_safety(&option([4; 7]), &input);
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].rule, SafetyRule::WeeklyDoseChange);
+        assert_eq!(warnings[0].severity, Severity::Critical);
+        assert_eq!(warnings[1].rule, SafetyRule::PillBurden);
+        assert_eq!(warnings[1].severity, Severity::Info);
+    }
+
+    #[test]
+    fn predicted_inr_outside_the_target() {
+        let target = InrTarget {
+            low: 2.0,
+            high: 3.0,
+        };
+        let prediction = |predicted_inr, extrapolated| InrPrediction {
+            predicted_inr,
+            lower_95: predicted_inr - 0.5,
+            upper_95: predicted_inr + 0.5,
+            extrapolated,
+        };
+        assert!(check_predicted_inr(&prediction(2.5, false), &target).is_none());
+        let high = check_predicted_inr(&prediction(3.4, false), &target).unwrap();
+        assert_eq!(high.severity, Severity::Warning);
+        assert!(high.message.contains("สูงกว่า"));
+        let low = check_predicted_inr(&prediction(1.6, true), &target).unwrap();
+        assert_eq!(low.severity, Severity::Info);
+        assert!(low.message.contains("ต่ำกว่า"));
+    }
+}
@@ -0,0 +1,139 @@
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{generate_options, prepare_calculation_input, stock_shortfalls_for, CalculationInput};
+
+// Doses further than this from the target are not offered as a snap.
+const MAX_SNAP_DEVIATION: f64 = 0.25;
+
+/// An achievable weekly dose and how far it lies from the requested one.
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct AchievableDose {
+    pub weekly_dose: f64,
+    pub deviation_percent: f64, // negative below the target
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SnappedDose {
+    pub target_weekly_dose: f64,
+    pub below: Option<AchievableDose>, // the target itself when it is achievable
+    pub above: Option<AchievableDose>,
+    pub nearest: Option<AchievableDose>, // the lower one on a tie
+}
+
+#[wasm_bindgen]
+pub fn snap_weekly_dose_rust(input_js: JsValue) -> Result<JsValue, JsValue> {
+    let mut input: CalculationInput =
+        serde_wasm_bindgen::from_value(input_js).map_err(|e| e.to_string())?;
+    prepare_calculation_input(&mut input)?;
+    Ok(serde_wasm_bindgen::to_value(&snap_weekly_dose(&input))?)
+}
+
+/// The nearest weekly doses at or below and at or above `input.weekly_dose` that
+/// the generator can build from the available tablets under the same settings,
+/// searched along the drug's dose step within 25% of the target and up to its
+/// weekly ceiling.
+pub fn snap_weekly_dose(input: &CalculationInput) -> SnappedDose {
+    let target = input.weekly_dose;
+    let profile = input.drug.profile();
+    let step = profile.dose_step_mg;
+    let highest_step = (profile.max_daily_dose_mg * 7.0 / step).round() as i64;
+
+    let achievable = |steps: i64| {
+        let weekly_dose = steps as f64 * step;
+        has_regimen(input, weekly_dose).then_some(AchievableDose {
+            weekly_dose,
+            deviation_percent: if target > 0.0 {
+                (weekly_dose - target) / target * 100.0
+            } else {
+                0.0
+            },
+        })
+    };
+    // A week without any dose only counts when that is what was asked for.
+    let lowest_step =
+        i64::from(target > 0.0).max((target * (1.0 - MAX_SNAP_DEVIATION) / step).ceil() as i64);
+    let highest_step =
+        highest_step.min((target * (1.0 + MAX_SNAP_DEVIATION) / step).floor() as i64);
+    let below = (lowest_step..=((target / step).floor() as i64).min(highest_step))
+        .rev()
+        .find_map(achievable);
+    let above =
+        (((target / step).ceil() as i64).max(lowest_step)..=highest_step).find_map(achievable);
+    let nearest = match (below, above) {
+        (Some(b), Some(a)) if a.deviation_percent < -b.deviation_percent => Some(a),
+        (Some(b), _) => Some(b),
+        (None, a) => a,
+    };
+    SnappedDose {
+        target_weekly_dose: target,
+        below,
+        above,
+        nearest,
+    }
+}
+
+/// Whether `generate_suggestions` would return anything at `weekly_dose`.
+pub(crate) fn has_regimen(input: &CalculationInput, weekly_dose: f64) -> bool {
+    let candidate = CalculationInput {
+        weekly_dose,
+        ..input.clone()
+    };
+    let options = generate_options(&candidate);
+    if input.exclude_out_of_stock && !input.stock.is_empty() {
+        options
+            .iter()
+            .any(|opt| stock_shortfalls_for(opt, &candidate).is_empty())
+    } else {
+        !options.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 5 mg tablets, no halves.
+    fn snap(weekly_dose: f64) -> SnappedDose {
+        snap_weekly_dose(&CalculationInput {
+            weekly_dose,
+            available_pills: vec![5],
+            ..Default::default()
+        })
+    }
+
+    fn doses(snapped: &SnappedDose) -> [Option<f64>; 3] {
+        [snapped.below, snapped.above, snapped.nearest].map(|d| d.map(|d| d.weekly_dose))
+    }
+
+    #[test]
+    fn an_achievable_target_snaps_to_itself() {
+        assert_eq!(doses(&snap(35.0)), [Some(35.0); 3]);
+    }
+
+    #[test]
+    fn picks_the_closer_of_below_and_above() {
+        assert_eq!(doses(&snap(33.5)), [Some(32.5), Some(35.0), Some(32.5)]);
+        assert_eq!(doses(&snap(34.0)), [Some(32.5), Some(35.0), Some(35.0)]);
+        let snapped = snap(34.0);
+        assert!(snapped.below.unwrap().deviation_percent < 0.0);
+        assert!(snapped.above.unwrap().deviation_percent > 0.0);
+    }
+
+    #[test]
+    fn a_tie_goes_to_the_lower_dose() {
+        // 32.5 and 35.0 are both 1.25 mg from 33.75.
+        assert_eq!(doses(&snap(33.75)), [Some(32.5), Some(35.0), Some(32.5)]);
+    }
+
+    #[test]
+    fn a_target_below_every_achievable_dose_has_no_below() {
+        assert_eq!(doses(&snap(17.0)), [None, Some(20.0), Some(20.0)]);
+    }
+
+    #[test]
+    fn does_not_offer_doses_far_from_the_target() {
+        // 20 mg is the lowest 5 mg tablets allow, 1900% above 1 mg.
+        assert_eq!(doses(&snap(1.0)), [None; 3]);
+    }
+}
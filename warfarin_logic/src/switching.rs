@@ -0,0 +1,472 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::date::CalendarDate;
+use crate::drug::{VkaDrug, VkaProfile};
+use crate::indication::{Indication, InrTargetRequest};
+use crate::plan::{day_label, regimen_from_schedule, summarize_plan_pills, PlanPhase, PlannedDay};
+use crate::{
+    combo_dose, nearest_achievable_option, render_option, CalculationInput, DaySchedule,
+    FinalOutput, Pill, SpecialDayPattern, TotalPillsSummary,
+};
+
+// How far (as a fraction of the equivalent weekly dose) the new VKA's dose may
+// drift to land on a dose the selected tablets can build.
+const EQUIVALENT_DOSE_SEARCH_WINDOW: f64 = 0.1;
+// INR checks after starting a new VKA, in days from its first dose.
+const NEW_VKA_INR_CHECK_DAYS: [i64; 2] = [3, 7];
+// The INR is first checked after about 1.5 half-lives without a dose, then daily.
+const WASHOUT_HALF_LIVES: f64 = 1.5;
+const DOAC_DAILY_INR_CHECKS: i64 = 3;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Doac {
+    Apixaban,
+    Dabigatran,
+    Edoxaban,
+    Rivaroxaban,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum SwitchTo {
+    Vka(VkaDrug),
+    Doac(Doac),
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SwitchInput {
+    pub visit_date: CalendarDate, // the current drug's last dose, unless the two overlap
+    #[serde(default)]
+    pub from: VkaDrug,
+    pub to: SwitchTo,
+    pub current_regimen: Vec<DaySchedule>,
+    #[serde(flatten)]
+    pub target: InrTargetRequest,
+    // Used when switching to another VKA.
+    #[serde(default)]
+    pub allow_half: bool,
+    #[serde(default)]
+    pub available_pills: Vec<u8>, // defaults to every strength of the new drug
+    #[serde(default)]
+    pub special_day_pattern: SpecialDayPattern,
+    pub days_until_appointment: u32,
+}
+
+/// Start the new drug once the INR is below (or at, when `inclusive`) this value.
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct InrThreshold {
+    pub inr: f64,
+    pub inclusive: bool,
+}
+
+impl InrThreshold {
+    pub fn allows_start(&self, inr: f64) -> bool {
+        inr < self.inr || (self.inclusive && inr == self.inr)
+    }
+}
+
+#[derive(Serialize)]
+pub struct SwitchPlan {
+    pub description: String,
+    pub last_dose_date: CalendarDate, // the earliest, when the old drug waits on an INR
+    pub current_weekly_dose: f64,
+    pub equivalent_weekly_dose: Option<f64>, // before rounding to an achievable dose
+    pub new_regimen: Option<FinalOutput>,
+    pub new_drug_start: CalendarDate, // the earliest start when it waits on an INR
+    pub start_inr_threshold: Option<InrThreshold>,
+    pub inr_checks: Vec<CalendarDate>,
+    pub days: Vec<PlannedDay>,
+    pub total_pills_summary: TotalPillsSummary, // tablets of the new VKA only
+}
+
+#[wasm_bindgen]
+pub fn plan_anticoagulant_switch_rust(input_js: JsValue) -> Result<JsValue, JsValue> {
+    let input: SwitchInput = serde_wasm_bindgen::from_value(input_js).map_err(|e| e.to_string())?;
+    let plan = plan_anticoagulant_switch(&input)?;
+    Ok(serde_wasm_bindgen::to_value(&plan)?)
+}
+
+/// Plans a switch away from the current VKA. Another VKA starts at the equivalent
+/// weekly dose, after a gap when the old drug outlasts the new one or overlapping it
+/// until the INR reaches the range when the new one is slower; a DOAC starts once
+/// the INR falls below its threshold.
+pub fn plan_anticoagulant_switch(input: &SwitchInput) -> Result<SwitchPlan, String> {
+    let cycle = regimen_from_schedule(&input.current_regimen)?;
+    let current_weekly_dose =
+        cycle.iter().map(|combo| combo_dose(combo)).sum::<f64>() / (cycle.len() / 7) as f64;
+    let from = input.from.profile();
+    let last_dose_date = input.visit_date;
+
+    // The current regimen is assumed to be in week A during the visit week.
+    let visit_day_of_week = last_dose_date.day_of_week();
+    let current_combo = |offset: usize| &cycle[(visit_day_of_week + offset) % cycle.len()];
+    let mut last_day = PlannedDay::new(last_dose_date, PlanPhase::CurrentRegimen, current_combo(0));
+
+    match input.to {
+        SwitchTo::Vka(drug) => switch_to_vka(
+            input,
+            drug.profile(),
+            current_weekly_dose,
+            last_day,
+            current_combo,
+        ),
+        SwitchTo::Doac(doac) => {
+            last_day.notes.push(format!("{}มื้อสุดท้าย", from.name));
+            Ok(switch_to_doac(input, doac, current_weekly_dose, last_day))
+        }
+    }
+}
+
+fn switch_to_vka<'a>(
+    input: &SwitchInput,
+    to: &VkaProfile,
+    current_weekly_dose: f64,
+    mut last_day: PlannedDay,
+    current_combo: impl Fn(usize) -> &'a Vec<Pill>,
+) -> Result<SwitchPlan, String> {
+    let from = input.from.profile();
+    if from.drug == to.drug {
+        return Err("ยาเดิมและยาใหม่เป็นยาตัวเดียวกัน".to_string());
+    }
+    let target = input.target.resolve()?;
+    let visit_date = last_day.date;
+
+    // Leave a gap for the excess half-life of the old drug so the two do not add up.
+    let gap_days = ((from.half_life_hours - to.half_life_hours) / 24.0)
+        .floor()
+        .max(0.0) as i64;
+    let new_drug_start = visit_date.add_days(1 + gap_days);
+    // An old drug that wears off faster than the new one takes effect keeps going
+    // alongside it until the first INR check finds the INR in range.
+    let overlap_until = (from.half_life_hours < to.half_life_hours)
+        .then(|| new_drug_start.add_days(NEW_VKA_INR_CHECK_DAYS[0]));
+    let last_dose_date = overlap_until.unwrap_or(visit_date);
+    if overlap_until.is_none() {
+        last_day.notes.push(format!("{}มื้อสุดท้าย", from.name));
+    }
+    let start_offset = visit_date.days_until(new_drug_start);
+    if input.days_until_appointment as i64 <= start_offset {
+        return Err(format!(
+            "วันนัดต้องอยู่หลังวันเริ่ม{} ({})",
+            to.name,
+            day_label(new_drug_start)
+        ));
+    }
+
+    let mut available_pills = if input.available_pills.is_empty() {
+        to.strengths_mg.to_vec()
+    } else {
+        input.available_pills.clone()
+    };
+    available_pills.sort_unstable_by(|a, b| b.cmp(a));
+    to.check_strengths(&available_pills)?;
+    let mut regimen_input = CalculationInput {
+        drug: to.drug,
+        weekly_dose: 0.0,
+        allow_half: input.allow_half,
+        available_pills,
+        special_day_pattern: input.special_day_pattern,
+        days_until_appointment: input.days_until_appointment - start_offset as u32,
+        start_day_of_week: new_drug_start.day_of_week() as u8,
+        target: input.target,
+        ..Default::default()
+    };
+
+    let equivalent_weekly_dose =
+        current_weekly_dose * to.warfarin_dose_ratio / from.warfarin_dose_ratio;
+    let (option, new_weekly_dose) = nearest_achievable_option(
+        &regimen_input,
+        equivalent_weekly_dose,
+        equivalent_weekly_dose * EQUIVALENT_DOSE_SEARCH_WINDOW,
+        |_| true,
+    )
+    .ok_or_else(|| format!("ไม่พบขนาดยา{}ที่ใกล้เคียงซึ่งจัดได้ด้วยเม็ดยาที่เลือก", to.name))?;
+    regimen_input.weekly_dose = new_weekly_dose;
+    let new_regimen = render_option(&option, &regimen_input);
+
+    let start_inr_threshold = (gap_days > 0).then_some(InrThreshold {
+        inr: target.high,
+        inclusive: true,
+    });
+    let mut inr_checks = Vec::new();
+    if gap_days > 0 {
+        inr_checks.push(new_drug_start);
+    }
+    inr_checks.extend(
+        NEW_VKA_INR_CHECK_DAYS
+            .iter()
+            .map(|&day| new_drug_start.add_days(day)),
+    );
+
+    let mut days = vec![last_day];
+    for offset in 1..start_offset {
+        days.push(PlannedDay::new(
+            visit_date.add_days(offset),
+            PlanPhase::Interrupted,
+            &[],
+        ));
+    }
+    for (offset, combo) in option
+        .daily_combos(
+            regimen_input.start_day_of_week,
+            regimen_input.days_until_appointment,
+        )
+        .enumerate()
+    {
+        let date = new_drug_start.add_days(offset as i64);
+        let overlapping = overlap_until.is_some_and(|until| date <= until);
+        let phase = if overlapping {
+            PlanPhase::Overlap
+        } else {
+            PlanPhase::NewRegimen
+        };
+        let mut day = PlannedDay::new(date, phase, combo);
+        if offset == 0 {
+            day.notes.push(format!("เริ่ม{}", to.name));
+        }
+        if overlapping {
+            let old_dose = combo_dose(current_combo(start_offset as usize + offset));
+            day.notes
+                .push(format!("ร่วมกับ{} {:.1} mg", from.name, old_dose));
+        }
+        days.push(day);
+    }
+    for day in days.iter_mut() {
+        if Some(day.date) == overlap_until {
+            day.notes.push(format!(
+                "ตรวจ INR: หยุด{}เมื่อ INR ≥ {:.1} หากต่ำกว่าให้ใช้ร่วมกันต่อและตรวจซ้ำ",
+                from.name, target.low
+            ));
+        } else if day.date == new_drug_start && gap_days > 0 {
+            day.notes.push(format!(
+                "ตรวจ INR ก่อนเริ่มยา: เริ่มเมื่อ INR ≤ {:.1} หากสูงกว่าให้เลื่อนออกไป",
+                target.high
+            ));
+        } else if inr_checks.contains(&day.date) {
+            day.notes.push("ตรวจ INR".to_string());
+        }
+    }
+
+    let new_days: Vec<PlannedDay> = days
+        .iter()
+        .filter(|d| matches!(d.phase, PlanPhase::NewRegimen | PlanPhase::Overlap))
+        .cloned()
+        .collect();
+    let total_pills_summary = summarize_plan_pills(&new_days);
+
+    let mut parts = vec![
+        format!("{} มื้อสุดท้าย {}", from.name, day_label(last_dose_date)),
+        format!(
+            "เริ่ม{} {} ขนาด {:.1} mg/สัปดาห์",
+            to.name,
+            day_label(new_drug_start),
+            new_weekly_dose
+        ),
+    ];
+    if gap_days > 0 {
+        parts.insert(1, format!("เว้นยา {} วัน", gap_days));
+    }
+    if let Some(until) = overlap_until {
+        parts.remove(0);
+        parts.push(format!(
+            "ให้{}ร่วมกับ{}ถึง {} แล้วหยุดเมื่อ INR ≥ {:.1}",
+            from.name,
+            to.name,
+            day_label(until),
+            target.low
+        ));
+    }
+    parts.push(format!(
+        "ตรวจ INR {}",
+        inr_checks
+            .iter()
+            .map(|&date| day_label(date))
+            .collect::<Vec<_>>()
+            .join(", ")
+    ));
+
+    Ok(SwitchPlan {
+        description: parts.join(", "),
+        last_dose_date,
+        current_weekly_dose,
+        equivalent_weekly_dose: Some(equivalent_weekly_dose),
+        new_regimen: Some(new_regimen),
+        new_drug_start,
+        start_inr_threshold,
+        inr_checks,
+        days,
+        total_pills_summary,
+    })
+}
+
+fn switch_to_doac(
+    input: &SwitchInput,
+    doac: Doac,
+    current_weekly_dose: f64,
+    last_day: PlannedDay,
+) -> SwitchPlan {
+    let from = input.from.profile();
+    let last_dose_date = last_day.date;
+    let threshold = doac_start_threshold(doac, input.target.indication);
+    let name = doac_name(doac);
+
+    let first_check_offset =
+        ((from.half_life_hours * WASHOUT_HALF_LIVES / 24.0).ceil() as i64).max(1);
+    let inr_checks: Vec<CalendarDate> = (0..DOAC_DAILY_INR_CHECKS)
+        .map(|day| last_dose_date.add_days(first_check_offset + day))
+        .collect();
+    let new_drug_start = inr_checks[0];
+    let comparison = if threshold.inclusive { "≤" } else { "<" };
+
+    let mut days = vec![last_day];
+    for offset in 1..=first_check_offset + DOAC_DAILY_INR_CHECKS - 1 {
+        let mut day = PlannedDay::new(last_dose_date.add_days(offset), PlanPhase::Interrupted, &[]);
+        if inr_checks.contains(&day.date) {
+            day.notes.push(format!(
+                "ตรวจ INR: เริ่ม {} เมื่อ INR {} {:.1}",
+                name, comparison, threshold.inr
+            ));
+        }
+        days.push(day);
+    }
+
+    SwitchPlan {
+        description: format!(
+            "{} มื้อสุดท้าย {}, ตรวจ INR ทุกวันตั้งแต่ {} และเริ่ม {} เมื่อ INR {} {:.1}",
+            from.name,
+            day_label(last_dose_date),
+            day_label(new_drug_start),
+            name,
+            comparison,
+            threshold.inr
+        ),
+        last_dose_date,
+        current_weekly_dose,
+        equivalent_weekly_dose: None,
+        new_regimen: None,
+        new_drug_start,
+        start_inr_threshold: Some(threshold),
+        inr_checks,
+        days,
+        total_pills_summary: summarize_plan_pills(&[]),
+    }
+}
+
+fn doac_start_threshold(doac: Doac, indication: Option<Indication>) -> InrThreshold {
+    match doac {
+        Doac::Apixaban | Doac::Dabigatran => InrThreshold {
+            inr: 2.0,
+            inclusive: false,
+        },
+        Doac::Edoxaban => InrThreshold {
+            inr: 2.5,
+            inclusive: true,
+        },
+        Doac::Rivaroxaban if indication == Some(Indication::AtrialFibrillation) => InrThreshold {
+            inr: 3.0,
+            inclusive: false,
+        },
+        Doac::Rivaroxaban => InrThreshold {
+            inr: 2.5,
+            inclusive: true,
+        },
+    }
+}
+
+fn doac_name(doac: Doac) -> &'static str {
+    match doac {
+        Doac::Apixaban => "apixaban",
+        Doac::Dabigatran => "dabigatran",
+        Doac::Edoxaban => "edoxaban",
+        Doac::Rivaroxaban => "rivaroxaban",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PillRenderData;
+
+    fn input(from: VkaDrug, to: VkaDrug, mg: u8) -> SwitchInput {
+        SwitchInput {
+            visit_date: CalendarDate::from_ymd(2026, 10, 14).unwrap(),
+            from,
+            to: SwitchTo::Vka(to),
+            current_regimen: (0..7)
+                .map(|day_index| DaySchedule {
+                    day_index,
+                    week_index: 0,
+                    total_dose: mg as f64,
+                    pills: vec![PillRenderData {
+                        mg,
+                        count: 1,
+                        is_half: false,
+                    }],
+                    is_stop_day: false,
+                    is_special_day: false,
+                })
+                .collect(),
+            target: InrTargetRequest::default(),
+            allow_half: true,
+            available_pills: Vec::new(),
+            special_day_pattern: SpecialDayPattern::default(),
+            days_until_appointment: 14,
+        }
+    }
+
+    #[test]
+    fn shorter_acting_old_drug_overlaps_until_the_inr_check() {
+        let plan = plan_anticoagulant_switch(&input(VkaDrug::Acenocoumarol, VkaDrug::Warfarin, 4))
+            .unwrap();
+        let start = CalendarDate::from_ymd(2026, 10, 15).unwrap();
+        let overlap_end = start.add_days(NEW_VKA_INR_CHECK_DAYS[0]);
+        assert_eq!(plan.new_drug_start, start);
+        assert_eq!(plan.last_dose_date, overlap_end);
+        assert!(plan.inr_checks.contains(&overlap_end));
+        let overlap: Vec<&PlannedDay> = plan
+            .days
+            .iter()
+            .filter(|d| d.phase == PlanPhase::Overlap)
+            .collect();
+        assert_eq!(overlap.len(), 4);
+        assert_eq!(overlap[0].date, start);
+        assert!(overlap.iter().all(|d| d.total_dose > 0.0));
+        assert!(overlap[3].notes.iter().any(|n| n.contains("INR")));
+    }
+
+    #[test]
+    fn longer_acting_old_drug_leaves_a_gap() {
+        let plan = plan_anticoagulant_switch(&input(VkaDrug::Phenprocoumon, VkaDrug::Warfarin, 3))
+            .unwrap();
+        assert_eq!(
+            plan.last_dose_date,
+            CalendarDate::from_ymd(2026, 10, 14).unwrap()
+        );
+        assert!(plan.new_drug_start > plan.last_dose_date.add_days(1));
+        assert!(plan.days.iter().all(|d| d.phase != PlanPhase::Overlap));
+        assert!(plan.start_inr_threshold.is_some());
+    }
+
+    #[test]
+    fn rivaroxaban_for_af_waits_for_an_inr_below_3() {
+        let input = SwitchInput {
+            to: SwitchTo::Doac(Doac::Rivaroxaban),
+            target: InrTargetRequest {
+                indication: Some(Indication::AtrialFibrillation),
+                ..Default::default()
+            },
+            ..input(VkaDrug::Warfarin, VkaDrug::Warfarin, 3)
+        };
+        let plan = plan_anticoagulant_switch(&input).unwrap();
+        let threshold = plan.start_inr_threshold.unwrap();
+        assert!(!threshold.allows_start(3.0));
+        assert!(threshold.allows_start(2.9));
+        assert!(plan.description.contains("INR < 3.0"));
+
+        // Other indications start at an INR of 2.5 or less.
+        let threshold = doac_start_threshold(Doac::Rivaroxaban, None);
+        assert!(threshold.allows_start(2.5));
+    }
+}
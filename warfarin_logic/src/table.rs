@@ -0,0 +1,114 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TableFormat {
+    Html,
+    Csv,
+    Text,
+}
+
+/// A printable table: one header row, then one row per entry with an optional
+/// HTML class to highlight it.
+pub(crate) struct Table<const N: usize> {
+    pub headers: [&'static str; N],
+    pub rows: Vec<([String; N], Option<&'static str>)>,
+}
+
+impl<const N: usize> Table<N> {
+    pub(crate) fn render(&self, format: TableFormat) -> String {
+        match format {
+            TableFormat::Html => self.render_html(),
+            TableFormat::Csv => self.render_csv(),
+            TableFormat::Text => self.render_text(),
+        }
+    }
+
+    fn all_rows(&self) -> impl Iterator<Item = [String; N]> + '_ {
+        std::iter::once(self.headers.map(String::from))
+            .chain(self.rows.iter().map(|(cells, _)| cells.clone()))
+    }
+
+    fn render_html(&self) -> String {
+        let escape = |text: &str| {
+            text.replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+        };
+        let mut html = String::from("<table>\n<thead><tr>");
+        for header in self.headers {
+            html.push_str(&format!("<th>{}</th>", escape(header)));
+        }
+        html.push_str("</tr></thead>\n<tbody>\n");
+        for (cells, class) in &self.rows {
+            match class {
+                Some(class) => html.push_str(&format!("<tr class=\"{}\">", class)),
+                None => html.push_str("<tr>"),
+            }
+            for cell in cells {
+                html.push_str(&format!("<td>{}</td>", escape(cell)));
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</tbody>\n</table>\n");
+        html
+    }
+
+    fn render_csv(&self) -> String {
+        let quote = |text: &str| {
+            if text.contains([',', '"', '\n']) {
+                format!("\"{}\"", text.replace('"', "\"\""))
+            } else {
+                text.to_string()
+            }
+        };
+        self.all_rows()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| quote(cell))
+                    .collect::<Vec<_>>()
+                    .join(",")
+                    + "\n"
+            })
+            .collect()
+    }
+
+    /// Columns padded for a terminal.
+    fn render_text(&self) -> String {
+        let rows: Vec<[String; N]> = self.all_rows().collect();
+        let widths: Vec<usize> = (0..N)
+            .map(|col| {
+                rows.iter()
+                    .map(|row| display_width(&row[col]))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let mut text = String::new();
+        for (i, row) in rows.iter().enumerate() {
+            let line = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, &width)| {
+                    format!("{}{}", cell, " ".repeat(width - display_width(cell)))
+                })
+                .collect::<Vec<_>>()
+                .join("  ");
+            text.push_str(line.trim_end());
+            text.push('\n');
+            if i == 0 {
+                let rule = widths.iter().map(|&w| "-".repeat(w)).collect::<Vec<_>>();
+                text.push_str(&rule.join("  "));
+                text.push('\n');
+            }
+        }
+        text
+    }
+}
+
+// Thai vowel and tone marks above or below a consonant take no column of their own.
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .filter(|&c| !matches!(c, '\u{0E31}' | '\u{0E34}'..='\u{0E3A}' | '\u{0E47}'..='\u{0E4E}'))
+        .count()
+}
@@ -0,0 +1,289 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::date::CalendarDate;
+use crate::drug::VkaDrug;
+use crate::indication::InrTargetRequest;
+use crate::plan::{
+    boosted_combo, day_label, regimen_from_schedule, summarize_plan_pills, PlanPhase, PlannedDay,
+};
+use crate::{combo_dose, DaySchedule, TotalPillsSummary};
+
+// An INR this far above the range calls for a held dose, this far below for a boost.
+const HOLD_INR_MARGIN: f64 = 1.0;
+const BOOST_INR_MARGIN: f64 = 0.5;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TransitionStart {
+    /// The visit day's dose already follows the new regimen.
+    #[serde(rename = "today")]
+    Today,
+    /// The visit day's dose follows the current regimen; the new one starts tomorrow.
+    #[default]
+    #[serde(rename = "tomorrow")]
+    Tomorrow,
+    /// Finish this week on the current regimen and start the new one on Monday.
+    #[serde(rename = "next-week")]
+    NextWeek,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DoseAdjustment {
+    None,
+    Hold,
+    Boost,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TransitionInput {
+    #[serde(default)]
+    pub drug: VkaDrug,
+    pub current_regimen: Vec<DaySchedule>,
+    pub new_regimen: Vec<DaySchedule>,
+    pub visit_date: CalendarDate,
+    #[serde(default)]
+    pub start: TransitionStart,
+    pub inr: Option<f64>,
+    #[serde(flatten)]
+    pub target: InrTargetRequest,
+    pub days_until_appointment: u32,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TransitionPlan {
+    pub description: String,
+    pub new_regimen_start: CalendarDate,
+    pub adjustment: DoseAdjustment,
+    pub adjustment_date: Option<CalendarDate>,
+    pub days: Vec<PlannedDay>,
+    pub total_pills_summary: TotalPillsSummary,
+}
+
+#[wasm_bindgen]
+pub fn plan_dose_transition_rust(input_js: JsValue) -> Result<JsValue, JsValue> {
+    let input: TransitionInput =
+        serde_wasm_bindgen::from_value(input_js).map_err(|e| e.to_string())?;
+    let plan = plan_dose_transition(&input)?;
+    Ok(serde_wasm_bindgen::to_value(&plan)?)
+}
+
+/// Lays out the days from the visit until the appointment: the current regimen up
+/// to the changeover, then the new regimen, with its first dosing day held or
+/// boosted when the INR is well outside the target range.
+pub fn plan_dose_transition(input: &TransitionInput) -> Result<TransitionPlan, String> {
+    let current_cycle = regimen_from_schedule(&input.current_regimen)?;
+    let new_cycle = regimen_from_schedule(&input.new_regimen)?;
+    let target = input.target.resolve()?;
+
+    let visit_day_of_week = input.visit_date.day_of_week();
+    let new_start_offset = match input.start {
+        TransitionStart::Today => 0,
+        TransitionStart::Tomorrow => 1,
+        TransitionStart::NextWeek => 7 - visit_day_of_week,
+    };
+    let new_regimen_start = input.visit_date.add_days(new_start_offset as i64);
+
+    let mut adjustment = match input.inr {
+        Some(inr) if inr >= target.high + HOLD_INR_MARGIN => DoseAdjustment::Hold,
+        Some(inr) if inr < target.low - BOOST_INR_MARGIN => DoseAdjustment::Boost,
+        _ => DoseAdjustment::None,
+    };
+    let mut adjustment_date = None;
+
+    let mut days = Vec::new();
+    for offset in 0..input.days_until_appointment as usize {
+        let date = input.visit_date.add_days(offset as i64);
+        if offset < new_start_offset {
+            // The current regimen is assumed to be in week A during the visit week.
+            let combo = &current_cycle[(visit_day_of_week + offset) % current_cycle.len()];
+            days.push(PlannedDay::new(date, PlanPhase::CurrentRegimen, combo));
+            continue;
+        }
+
+        // The new regimen's week A starts on the Monday of the week it starts in.
+        let cycle_day =
+            (offset - new_start_offset + new_regimen_start.day_of_week()) % new_cycle.len();
+        let combo = &new_cycle[cycle_day];
+        let is_dosing_day = combo_dose(combo) > 0.0;
+        if adjustment_date.is_none() && adjustment != DoseAdjustment::None && is_dosing_day {
+            match adjustment {
+                DoseAdjustment::Hold => {
+                    days.push(PlannedDay::new(date, PlanPhase::Hold, &[]));
+                    adjustment_date = Some(date);
+                    continue;
+                }
+                DoseAdjustment::Boost => {
+                    if let Some(boosted) = boosted_combo(combo, input.drug.profile()) {
+                        days.push(PlannedDay::new(date, PlanPhase::Boost, &boosted));
+                        adjustment_date = Some(date);
+                        continue;
+                    }
+                    adjustment = DoseAdjustment::None;
+                }
+                DoseAdjustment::None => {}
+            }
+        }
+        days.push(PlannedDay::new(date, PlanPhase::NewRegimen, combo));
+    }
+    if adjustment_date.is_none() {
+        adjustment = DoseAdjustment::None;
+    }
+
+    let description = describe_transition(&days, new_regimen_start, adjustment, adjustment_date);
+    let total_pills_summary = summarize_plan_pills(&days);
+
+    Ok(TransitionPlan {
+        description,
+        new_regimen_start,
+        adjustment,
+        adjustment_date,
+        days,
+        total_pills_summary,
+    })
+}
+
+fn describe_transition(
+    days: &[PlannedDay],
+    new_regimen_start: CalendarDate,
+    adjustment: DoseAdjustment,
+    adjustment_date: Option<CalendarDate>,
+) -> String {
+    let mut parts = Vec::new();
+    let current_days: Vec<String> = days
+        .iter()
+        .filter(|d| d.phase == PlanPhase::CurrentRegimen)
+        .map(|d| day_label(d.date))
+        .collect();
+    if !current_days.is_empty() {
+        parts.push(format!("ใช้ขนาดยาเดิม ({})", current_days.join(", ")));
+    }
+    parts.push(format!("เริ่มขนาดยาใหม่ {}", day_label(new_regimen_start)));
+    match (adjustment, adjustment_date) {
+        (DoseAdjustment::Hold, Some(date)) => {
+            parts.push(format!("งดยา 1 วัน ({})", day_label(date)))
+        }
+        (DoseAdjustment::Boost, Some(date)) => {
+            parts.push(format!("เพิ่มยาเป็น 2 เท่า 1 วัน ({})", day_label(date)))
+        }
+        _ => {}
+    }
+    parts.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PillRenderData;
+
+    // Week A 3 mg, week B 2 mg, every day.
+    fn two_week_regimen() -> Vec<DaySchedule> {
+        (0..14)
+            .map(|day| {
+                let mg = if day < 7 { 3 } else { 2 };
+                DaySchedule {
+                    day_index: day % 7,
+                    week_index: day / 7,
+                    total_dose: mg as f64,
+                    pills: vec![PillRenderData {
+                        mg,
+                        count: 1,
+                        is_half: false,
+                    }],
+                    is_stop_day: false,
+                    is_special_day: false,
+                }
+            })
+            .collect()
+    }
+
+    fn plan_with_inr(
+        start: TransitionStart,
+        visit_date: CalendarDate,
+        inr: Option<f64>,
+    ) -> TransitionPlan {
+        plan_dose_transition(&TransitionInput {
+            drug: VkaDrug::default(),
+            current_regimen: two_week_regimen(),
+            new_regimen: two_week_regimen(),
+            visit_date,
+            start,
+            inr,
+            target: InrTargetRequest::default(),
+            days_until_appointment: 21,
+        })
+        .unwrap()
+    }
+
+    fn plan(start: TransitionStart, visit_date: CalendarDate) -> TransitionPlan {
+        plan_with_inr(start, visit_date, None)
+    }
+
+    fn new_regimen_doses(plan: &TransitionPlan) -> Vec<f64> {
+        plan.days
+            .iter()
+            .filter(|d| d.phase == PlanPhase::NewRegimen)
+            .map(|d| d.total_dose)
+            .collect()
+    }
+
+    #[test]
+    fn two_week_regimen_starts_on_week_a_next_week() {
+        // Wednesday; the new regimen starts on Monday 19/10.
+        let visit = CalendarDate::from_ymd(2026, 10, 14).unwrap();
+        let plan = plan(TransitionStart::NextWeek, visit);
+        assert_eq!(
+            plan.new_regimen_start,
+            CalendarDate::from_ymd(2026, 10, 19).unwrap()
+        );
+        let doses = new_regimen_doses(&plan);
+        assert!(doses[..7].iter().all(|&d| d == 3.0));
+        assert!(doses[7..14].iter().all(|&d| d == 2.0));
+    }
+
+    #[test]
+    fn two_week_regimen_starts_on_week_a_next_day() {
+        // Wednesday: Thursday to Sunday fall in week A, then week B from Monday.
+        let visit = CalendarDate::from_ymd(2026, 10, 14).unwrap();
+        let doses = new_regimen_doses(&plan(TransitionStart::Tomorrow, visit));
+        assert!(doses[..4].iter().all(|&d| d == 3.0));
+        assert!(doses[4..11].iter().all(|&d| d == 2.0));
+
+        // Sunday: the new regimen starts on Monday with a full week A.
+        let visit = CalendarDate::from_ymd(2026, 10, 18).unwrap();
+        let doses = new_regimen_doses(&plan(TransitionStart::Tomorrow, visit));
+        assert!(doses[..7].iter().all(|&d| d == 3.0));
+        assert!(doses[7..14].iter().all(|&d| d == 2.0));
+    }
+
+    #[test]
+    fn high_inr_holds_the_first_new_regimen_day() {
+        let visit = CalendarDate::from_ymd(2026, 10, 14).unwrap();
+        let plan = plan_with_inr(TransitionStart::Tomorrow, visit, Some(4.5));
+        assert_eq!(plan.adjustment, DoseAdjustment::Hold);
+        assert_eq!(plan.adjustment_date, Some(visit.add_days(1)));
+        assert_eq!(plan.days[0].phase, PlanPhase::CurrentRegimen);
+        assert_eq!(plan.days[1].phase, PlanPhase::Hold);
+        assert_eq!(plan.days[1].total_dose, 0.0);
+        assert_eq!(plan.days[2].phase, PlanPhase::NewRegimen);
+    }
+
+    #[test]
+    fn low_inr_boosts_the_first_new_regimen_day() {
+        let visit = CalendarDate::from_ymd(2026, 10, 14).unwrap();
+        let plan = plan_with_inr(TransitionStart::Today, visit, Some(1.2));
+        assert_eq!(plan.adjustment, DoseAdjustment::Boost);
+        assert_eq!(plan.days[0].phase, PlanPhase::Boost);
+        assert_eq!(plan.days[0].total_dose, 6.0);
+        assert_eq!(plan.days[1].total_dose, 3.0);
+    }
+
+    #[test]
+    fn inr_in_range_needs_no_adjustment() {
+        let visit = CalendarDate::from_ymd(2026, 10, 14).unwrap();
+        let plan = plan_with_inr(TransitionStart::Tomorrow, visit, Some(2.5));
+        assert_eq!(plan.adjustment, DoseAdjustment::None);
+        assert_eq!(plan.adjustment_date, None);
+        assert_eq!(plan.days.len(), 21);
+    }
+}
@@ -0,0 +1,265 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::date::CalendarDate;
+use crate::indication::{InrTarget, InrTargetRequest};
+
+const DEFAULT_MAX_GAP_DAYS: u32 = 56;
+const HIGH_INR: f64 = 4.0;
+const VERY_HIGH_INR: f64 = 5.0;
+const LOW_INR: f64 = 1.5;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct InrResult {
+    pub date: CalendarDate,
+    pub inr: f64,
+    #[serde(flatten)]
+    pub target: InrTargetRequest, // falls back to the input's target when empty
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TtrInput {
+    pub results: Vec<InrResult>,
+    #[serde(flatten)]
+    pub target: InrTargetRequest,
+    #[serde(default = "default_max_gap_days")]
+    pub max_gap_days: u32,
+}
+
+fn default_max_gap_days() -> u32 {
+    DEFAULT_MAX_GAP_DAYS
+}
+
+/// Two consecutive tests too far apart to interpolate between.
+#[derive(Serialize, Debug, Clone)]
+pub struct TestGap {
+    pub from: CalendarDate,
+    pub to: CalendarDate,
+    pub days: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TtrResult {
+    pub ttr_percent: Option<f64>, // None when no interval could be interpolated
+    pub days_in_range: f64,
+    pub days_above_range: f64,
+    pub days_below_range: f64,
+    pub days_interpolated: i64,
+    pub inr_count: usize,
+    pub percent_inrs_in_range: Option<f64>,
+    pub count_above_4: usize,
+    pub count_above_5: usize,
+    pub count_below_1_5: usize,
+    pub excluded_gaps: Vec<TestGap>,
+}
+
+#[wasm_bindgen]
+pub fn calculate_ttr_rust(input_js: JsValue) -> Result<JsValue, JsValue> {
+    let input: TtrInput = serde_wasm_bindgen::from_value(input_js).map_err(|e| e.to_string())?;
+    Ok(serde_wasm_bindgen::to_value(&calculate_ttr(&input)?)?)
+}
+
+/// Time in therapeutic range by Rosendaal linear interpolation between consecutive
+/// tests, using the target range in force at the earlier test of each interval.
+/// Intervals longer than `max_gap_days` are left out and reported as gaps.
+pub fn calculate_ttr(input: &TtrInput) -> Result<TtrResult, String> {
+    let default_target = input.target.resolve()?;
+    if let Some(r) = input.results.iter().find(|r| !r.inr.is_finite()) {
+        return Err(format!("ค่า INR วันที่ {} ไม่ถูกต้อง", r.date));
+    }
+    let mut results = input
+        .results
+        .iter()
+        .map(|r| {
+            let target = if r.target.is_empty() {
+                default_target
+            } else {
+                r.target.resolve()?
+            };
+            Ok((r, target))
+        })
+        .collect::<Result<Vec<(&InrResult, InrTarget)>, String>>()?;
+    results.sort_by_key(|(r, _)| r.date);
+
+    let mut days_in_range = 0.0;
+    let mut days_above_range = 0.0;
+    let mut days_below_range = 0.0;
+    let mut days_interpolated = 0;
+    let mut excluded_gaps = Vec::new();
+
+    for pair in results.windows(2) {
+        let ((start, target), (end, _)) = (pair[0], pair[1]);
+        let days = start.date.days_until(end.date);
+        if days <= 0 {
+            continue;
+        }
+        if days > input.max_gap_days as i64 {
+            excluded_gaps.push(TestGap {
+                from: start.date,
+                to: end.date,
+                days,
+            });
+            continue;
+        }
+
+        let (below, inside, above) = segment_fractions(start.inr, end.inr, target.low, target.high);
+        days_below_range += below * days as f64;
+        days_in_range += inside * days as f64;
+        days_above_range += above * days as f64;
+        days_interpolated += days;
+    }
+
+    let inr_count = results.len();
+    let in_range_count = results
+        .iter()
+        .filter(|(r, target)| r.inr >= target.low && r.inr <= target.high)
+        .count();
+
+    Ok(TtrResult {
+        ttr_percent: (days_interpolated > 0)
+            .then(|| days_in_range / days_interpolated as f64 * 100.0),
+        days_in_range,
+        days_above_range,
+        days_below_range,
+        days_interpolated,
+        inr_count,
+        percent_inrs_in_range: (inr_count > 0)
+            .then(|| in_range_count as f64 / inr_count as f64 * 100.0),
+        count_above_4: results.iter().filter(|(r, _)| r.inr > HIGH_INR).count(),
+        count_above_5: results
+            .iter()
+            .filter(|(r, _)| r.inr > VERY_HIGH_INR)
+            .count(),
+        count_below_1_5: results.iter().filter(|(r, _)| r.inr < LOW_INR).count(),
+        excluded_gaps,
+    })
+}
+
+/// Fractions of a straight line from `from` to `to` that lie below, inside and
+/// above `[low, high]`.
+fn segment_fractions(from: f64, to: f64, low: f64, high: f64) -> (f64, f64, f64) {
+    if (to - from).abs() < f64::EPSILON {
+        return if from < low {
+            (1.0, 0.0, 0.0)
+        } else if from > high {
+            (0.0, 0.0, 1.0)
+        } else {
+            (0.0, 1.0, 0.0)
+        };
+    }
+    // Position along the segment (0..1) where the line reaches a given INR.
+    let position = |inr: f64| ((inr - from) / (to - from)).clamp(0.0, 1.0);
+    let (at_low, at_high) = (position(low), position(high));
+    let inside = (at_high - at_low).abs();
+    let below = if from < to { at_low } else { 1.0 - at_low };
+    (below, inside, 1.0 - below - inside)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(day: i64, inr: f64) -> InrResult {
+        InrResult {
+            date: CalendarDate::from_ymd(2026, 1, 1).unwrap().add_days(day),
+            inr,
+            target: InrTargetRequest::default(),
+        }
+    }
+
+    fn ttr(results: Vec<InrResult>) -> TtrResult {
+        calculate_ttr(&TtrInput {
+            results,
+            target: InrTargetRequest::default(),
+            max_gap_days: DEFAULT_MAX_GAP_DAYS,
+        })
+        .unwrap()
+    }
+
+    fn assert_days(result: &TtrResult, below: f64, inside: f64, above: f64) {
+        let days = (
+            result.days_below_range,
+            result.days_in_range,
+            result.days_above_range,
+        );
+        assert!(
+            (days.0 - below).abs() < 1e-9
+                && (days.1 - inside).abs() < 1e-9
+                && (days.2 - above).abs() < 1e-9,
+            "{:?}",
+            days
+        );
+    }
+
+    #[test]
+    fn interpolates_across_the_upper_bound() {
+        // Target 2.0–3.0: half of each 10-day interval is above 3.0.
+        assert_days(&ttr(vec![result(0, 2.5), result(10, 3.5)]), 0.0, 5.0, 5.0);
+        assert_days(&ttr(vec![result(0, 3.5), result(10, 2.5)]), 0.0, 5.0, 5.0);
+    }
+
+    #[test]
+    fn interpolates_across_the_lower_bound() {
+        assert_days(&ttr(vec![result(0, 1.5), result(10, 2.5)]), 5.0, 5.0, 0.0);
+        assert_days(&ttr(vec![result(0, 2.5), result(10, 1.5)]), 5.0, 5.0, 0.0);
+    }
+
+    #[test]
+    fn interpolates_across_both_bounds() {
+        let up = ttr(vec![result(0, 1.0), result(30, 4.0)]);
+        assert_days(&up, 10.0, 10.0, 10.0);
+        assert_eq!(up.ttr_percent.map(f64::round), Some(33.0));
+        assert_days(
+            &ttr(vec![result(0, 4.0), result(30, 1.0)]),
+            10.0,
+            10.0,
+            10.0,
+        );
+    }
+
+    #[test]
+    fn leaves_out_intervals_longer_than_max_gap_days() {
+        let result = ttr(vec![result(0, 2.5), result(10, 2.5), result(80, 4.0)]);
+        assert_eq!(result.days_interpolated, 10);
+        assert_eq!(result.ttr_percent, Some(100.0));
+        assert_eq!(result.excluded_gaps.len(), 1);
+        assert_eq!(result.excluded_gaps[0].days, 70);
+        assert_eq!(result.inr_count, 3);
+    }
+
+    #[test]
+    fn same_day_results_add_no_interpolated_days() {
+        let result = ttr(vec![result(0, 2.5), result(0, 3.5), result(10, 2.5)]);
+        assert_eq!(result.days_interpolated, 10);
+        assert_eq!(result.inr_count, 3);
+        assert_eq!(result.count_above_4, 0);
+        assert_eq!(result.percent_inrs_in_range.map(f64::round), Some(67.0));
+    }
+
+    #[test]
+    fn uses_the_target_in_force_at_each_result() {
+        let higher = InrTargetRequest {
+            target_inr_low: Some(2.5),
+            target_inr_high: Some(3.5),
+            ..Default::default()
+        };
+        let first = InrResult {
+            target: higher,
+            ..result(0, 3.2)
+        };
+        // 3.2 stays within 2.5–3.5 for the first interval, then is above 2.0–3.0.
+        let result = ttr(vec![first, result(10, 3.2), result(20, 3.2)]);
+        assert_days(&result, 0.0, 10.0, 10.0);
+        assert_eq!(result.percent_inrs_in_range.map(f64::round), Some(33.0));
+    }
+
+    #[test]
+    fn rejects_non_finite_inr() {
+        let input = TtrInput {
+            results: vec![result(0, 2.5), result(10, f64::NAN)],
+            target: InrTargetRequest::default(),
+            max_gap_days: DEFAULT_MAX_GAP_DAYS,
+        };
+        assert!(calculate_ttr(&input).is_err());
+    }
+}